@@ -0,0 +1,429 @@
+use crate::interval_tree::{IntervalError, IntervalTree};
+use crate::node::Range;
+use crate::piecewise_map::{PiecewiseMap, PiecewiseMapIter};
+
+use std::borrow::Borrow;
+use std::fmt;
+use std::ops::Add;
+use std::ops::RangeBounds;
+#[cfg(any(feature="serde", test))]
+use serde::{Deserialize, Serialize, Serializer};
+
+/// An additive interval map, in which weights added over intervals accumulate.
+/// The total weight at a point is the sum of the weights added over the intervals containing it.
+///
+/// Weights are stored as the disjoint segments of a [`PiecewiseMap`] with constant total weights,
+/// so that adding a weight only updates the segments it overlaps, and querying a point only
+/// needs a single lookup. Points outside of the segments have a total weight of zero,
+/// given by `W::default()`, and segments with a total weight of zero are never stored.
+///
+/// ```
+/// use std::ops::Bound::{Included, Excluded};
+/// use unbounded_interval_tree::additive_map::AdditiveMap;
+///
+/// let mut cpus = AdditiveMap::default();
+///
+/// cpus.add(9..17, 3);
+/// cpus.add(12..20, 2);
+///
+/// assert_eq!(cpus.value_at(&8), 0);
+/// assert_eq!(cpus.value_at(&12), 5);
+/// assert_eq!(cpus.profile(&(0..24)).collect::<Vec<_>>(), vec![
+///     ((Included(&9), Excluded(&12)), &3),
+///     ((Included(&12), Excluded(&17)), &5),
+///     ((Included(&17), Excluded(&20)), &2),
+/// ]);
+/// ```
+#[cfg_attr(any(feature="serde", test), derive(Deserialize))]
+#[cfg_attr(
+    any(feature="serde", test),
+    serde(
+        from = "PiecewiseMap<K, W>",
+        bound(deserialize = "K: Deserialize<'de> + Ord + Clone, W: Deserialize<'de> + Clone + PartialEq + Default")
+    )
+)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct AdditiveMap<K, W> {
+    // Every segment is associated with a non-zero total weight.
+    segments: PiecewiseMap<K, W>,
+}
+
+// An additive map is serialized as its segments, so that it is deserialized
+// through `From<PiecewiseMap<K, W>>`, which discards segments with a total weight of zero.
+#[cfg(any(feature="serde", test))]
+impl<K, W> Serialize for AdditiveMap<K, W>
+where
+    K: Serialize,
+    W: Serialize,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.segments.serialize(serializer)
+    }
+}
+
+impl<K, W> fmt::Display for AdditiveMap<K, W>
+where
+    K: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.segments)
+    }
+}
+
+impl<K, W> Default for AdditiveMap<K, W> {
+    fn default() -> AdditiveMap<K, W> {
+        AdditiveMap {
+            segments: PiecewiseMap::default(),
+        }
+    }
+}
+
+/// Creates an [`AdditiveMap`] from an iterator of pairs of elements
+/// satisfying the [`RangeBounds`] trait and the weights added over them.
+impl<K, W, R> FromIterator<(R, W)> for AdditiveMap<K, W>
+where
+    K: Ord + Clone,
+    W: Clone + PartialEq + Default + Add<Output = W>,
+    R: RangeBounds<K>,
+{
+    fn from_iter<T: IntoIterator<Item = (R, W)>>(iter: T) -> Self {
+        let mut additive_map = Self::default();
+
+        for (interval, delta) in iter {
+            additive_map.add(interval, delta);
+        }
+
+        additive_map
+    }
+}
+
+impl<K, W, R, const N: usize> From<[(R, W); N]> for AdditiveMap<K, W>
+where
+    K: Ord + Clone,
+    W: Clone + PartialEq + Default + Add<Output = W>,
+    R: RangeBounds<K>,
+{
+    fn from(entries: [(R, W); N]) -> Self {
+        entries.into_iter().collect()
+    }
+}
+
+/// Creates an [`AdditiveMap`] whose total weights are given by the segments of a [`PiecewiseMap`].
+/// Segments with a total weight of zero are discarded.
+///
+/// # Examples
+///
+/// ```
+/// use std::ops::Bound::{Included, Excluded};
+/// use unbounded_interval_tree::additive_map::AdditiveMap;
+/// use unbounded_interval_tree::piecewise_map::PiecewiseMap;
+///
+/// let segments = PiecewiseMap::from([(0..5, 2), (5..9, 0), (9..12, -1)]);
+/// let map = AdditiveMap::from(segments);
+///
+/// assert_eq!(map.len(), 2);
+/// assert_eq!(map.iter().collect::<Vec<_>>(), vec![
+///     (&(Included(0), Excluded(5)), &2),
+///     (&(Included(9), Excluded(12)), &-1),
+/// ]);
+/// ```
+impl<K, W> From<PiecewiseMap<K, W>> for AdditiveMap<K, W>
+where
+    K: Ord + Clone,
+    W: Clone + PartialEq + Default,
+{
+    fn from(segments: PiecewiseMap<K, W>) -> Self {
+        let zero = W::default();
+        let segments = segments
+            .iter()
+            .filter(|(_, weight)| **weight != zero)
+            .map(|(range, weight)| (range.clone(), weight.clone()))
+            .collect();
+
+        AdditiveMap { segments }
+    }
+}
+
+impl<K, W> AdditiveMap<K, W> {
+    /// Produces an inorder iterator over the segments of the map with a non-zero total weight,
+    /// along with their total weights.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::ops::Bound::{Included, Excluded};
+    /// use unbounded_interval_tree::additive_map::AdditiveMap;
+    ///
+    /// let map = AdditiveMap::from([(0..10, 1), (5..10, -1)]);
+    ///
+    /// let mut iter = map.iter();
+    /// assert_eq!(iter.next(), Some((&(Included(0), Excluded(5)), &1)));
+    /// assert_eq!(iter.next(), None);
+    /// ```
+    pub fn iter<'a>(&'a self) -> PiecewiseMapIter<'a, K, W> {
+        self.segments.iter()
+    }
+
+    /// Adds the weight `delta` to all the points of the interval `range`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::ops::Bound::{Included, Excluded};
+    /// use unbounded_interval_tree::additive_map::AdditiveMap;
+    ///
+    /// let mut map = AdditiveMap::default();
+    ///
+    /// map.add((Included(0), Excluded(10)), 2);
+    /// map.add((Excluded(5), Included(10)), 1);
+    ///
+    /// assert_eq!(map.value_at(&5), 2);
+    /// assert_eq!(map.value_at(&6), 3);
+    /// assert_eq!(map.value_at(&10), 1);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is inverted or empty, like [`IntervalTree::insert`].
+    /// See [`AdditiveMap::try_add`] to handle invalid intervals instead.
+    pub fn add<R>(&mut self, range: R, delta: W)
+    where
+        K: Ord + Clone,
+        W: Clone + PartialEq + Default + Add<Output = W>,
+        R: RangeBounds<K>,
+    {
+        if let Err(err) = self.try_add(range, delta) {
+            panic!("cannot add over an invalid interval: {}", err);
+        }
+    }
+
+    /// Adds the weight `delta` to all the points of the interval `range`, like [`AdditiveMap::add`],
+    /// unless `range` is invalid. See [`IntervalTree::try_insert`] for more details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unbounded_interval_tree::additive_map::AdditiveMap;
+    /// use unbounded_interval_tree::interval_tree::IntervalError;
+    ///
+    /// let mut map = AdditiveMap::default();
+    ///
+    /// assert_eq!(map.try_add(0..10, 1), Ok(()));
+    /// assert_eq!(map.try_add(5..5, 1), Err(IntervalError::Empty));
+    /// assert_eq!(map.value_at(&5), 1);
+    /// ```
+    pub fn try_add<R>(&mut self, range: R, delta: W) -> Result<(), IntervalError>
+    where
+        K: Ord + Clone,
+        W: Clone + PartialEq + Default + Add<Output = W>,
+        R: RangeBounds<K>,
+    {
+        let range = (range.start_bound().cloned(), range.end_bound().cloned());
+        IntervalTree::validate(&range)?;
+
+        if delta == W::default() {
+            return Ok(());
+        }
+
+        let updated = self
+            .segments
+            .iter_range(&range)
+            .map(|((start, end), weight)| ((start.cloned(), end.cloned()), weight.clone() + delta.clone()))
+            .collect::<Vec<_>>();
+
+        // The points of `range` outside of the segments get `delta`,
+        // and the ones in the segments get their updated total weight.
+        self.segments.assign(range, delta);
+        for (segment, weight) in updated {
+            if weight == W::default() {
+                self.segments.clear_range(segment);
+            } else {
+                self.segments.assign(segment, weight);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the total weight at the point `p`, that is the sum of the weights
+    /// added over the intervals containing it.
+    ///
+    /// The given point may be of a borrowed form of the stored type `K`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unbounded_interval_tree::additive_map::AdditiveMap;
+    ///
+    /// let map = AdditiveMap::from([(0..10, 1.5), (5..15, 2.0)]);
+    ///
+    /// assert_eq!(map.value_at(&-1), 0.0);
+    /// assert_eq!(map.value_at(&5), 3.5);
+    /// assert_eq!(map.value_at(&10), 2.0);
+    /// ```
+    pub fn value_at<Q>(&self, p: &Q) -> W
+    where
+        K: Ord + Borrow<Q>,
+        W: Clone + Default,
+        Q: Ord + ?Sized,
+    {
+        self.segments.get(p).cloned().unwrap_or_default()
+    }
+
+    /// Returns an inorder iterator over the segments with a non-zero total weight overlapping
+    /// with the given `range`, clipped to `range`, along with their total weights.
+    /// The points of `range` outside of the segments yielded have a total weight of zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::ops::Bound::{Included, Excluded};
+    /// use unbounded_interval_tree::additive_map::AdditiveMap;
+    ///
+    /// let map = AdditiveMap::from([(0..10, 1), (5..15, 2), (20..30, 3)]);
+    ///
+    /// assert_eq!(map.profile(&(8..=20)).collect::<Vec<_>>(), vec![
+    ///     ((Included(&8), Excluded(&10)), &3),
+    ///     ((Included(&10), Excluded(&15)), &2),
+    ///     ((Included(&20), Included(&20)), &3),
+    /// ]);
+    /// ```
+    pub fn profile<'a, R>(&'a self, range: &'a R) -> impl Iterator<Item = (Range<&'a K>, &'a W)>
+    where
+        K: Ord,
+        R: RangeBounds<K>,
+    {
+        self.segments.iter_range(range)
+    }
+
+    /// Returns the number of segments with a non-zero total weight.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unbounded_interval_tree::additive_map::AdditiveMap;
+    ///
+    /// let map = AdditiveMap::from([(0..10, 1), (5..15, 2)]);
+    ///
+    /// assert_eq!(map.len(), 3);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.segments.len()
+    }
+
+    /// Returns `true` if the total weight of every point is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unbounded_interval_tree::additive_map::AdditiveMap;
+    ///
+    /// let mut map = AdditiveMap::default();
+    ///
+    /// map.add(0..10, 1);
+    /// assert!(!map.is_empty());
+    ///
+    /// map.add(0..10, -1);
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.segments.is_empty()
+    }
+
+    /// Clear the map, resetting the total weight of every point to zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unbounded_interval_tree::additive_map::AdditiveMap;
+    ///
+    /// let mut map = AdditiveMap::from([(0..10, 1), (5..15, 2)]);
+    ///
+    /// map.clear();
+    ///
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn clear(&mut self) {
+        self.segments.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{intervals, points};
+    use serde_json::{from_str, to_string};
+    use std::ops::Bound::*;
+
+    #[test]
+    fn serialize_deserialize_identity() {
+        let mut map = AdditiveMap::default();
+        let serialized_empty_map = to_string(&map).unwrap();
+        let deserialized_empty_map = from_str(&serialized_empty_map).unwrap();
+        assert_eq!(map, deserialized_empty_map);
+
+        map.add((Unbounded, Excluded(3)), 2);
+        map.add((Excluded(1), Included(8)), -1);
+        let serialized_map = to_string(&map).unwrap();
+        let deserialized_map: AdditiveMap<_, _> = from_str(&serialized_map).unwrap();
+        assert_eq!(map, deserialized_map);
+        assert_eq!(deserialized_map.len(), 3);
+
+        // Segments with a total weight of zero are discarded.
+        let segments = PiecewiseMap::from([(0..5, 2), (5..9, 0), (9..12, -1)]);
+        let deserialized_map: AdditiveMap<i32, i32> = from_str(&to_string(&segments).unwrap()).unwrap();
+        assert_eq!(deserialized_map, AdditiveMap::from([(0..5, 2), (9..12, -1)]));
+        assert_eq!(deserialized_map.value_at(&7), 0);
+    }
+
+    #[test]
+    fn bounds_are_exact() {
+        let mut map = AdditiveMap::default();
+
+        map.add((Included(0), Excluded(10)), 1);
+        map.add((Excluded(10), Included(20)), 1);
+        map.add((Included(10), Included(10)), 2);
+        map.add((Excluded(20), Unbounded), 4);
+
+        assert_eq!(
+            map.iter().collect::<Vec<_>>(),
+            vec![
+                (&(Included(0), Excluded(10)), &1),
+                (&(Included(10), Included(10)), &2),
+                (&(Excluded(10), Included(20)), &1),
+                (&(Excluded(20), Unbounded), &4),
+            ]
+        );
+
+        map.add((Included(10), Included(10)), -1);
+        map.add(.., -1);
+
+        assert_eq!(
+            map.profile(&(Excluded(-5), Unbounded)).collect::<Vec<_>>(),
+            vec![
+                ((Excluded(&-5), Excluded(&0)), &-1),
+                ((Excluded(&20), Unbounded), &3),
+            ]
+        );
+    }
+
+    #[test]
+    fn random_operations_match_pointwise_sums() {
+        let mut map = AdditiveMap::default();
+        let mut sums = points(31, 4).map(|p| (p, 0)).collect::<Vec<_>>();
+        for (i, range) in intervals(300, 31, 4) {
+            let delta = (i % 5) - 2;
+            map.add(range, delta);
+            for (p, weight) in sums.iter_mut() {
+                if range.contains(p) {
+                    *weight += delta;
+                }
+            }
+
+            assert!(map.iter().all(|(_, weight)| *weight != 0));
+            for (p, weight) in &sums {
+                assert_eq!(map.value_at(p), *weight);
+            }
+        }
+    }
+}
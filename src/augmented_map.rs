@@ -0,0 +1,448 @@
+use crate::interval_map::IntervalMap;
+use crate::interval_tree::OverlapIter;
+use crate::node::{Node, Range};
+use crate::set_ops::Cut;
+
+use std::borrow::Borrow;
+use std::ops::Bound;
+use std::ops::Bound::*;
+use std::ops::RangeBounds;
+
+/// An aggregate maintained over every subtree of an [`IntervalMap`],
+/// such as the total weight, the number of intervals, or the maximum priority of its entries.
+///
+/// Aggregates form a monoid: [`Augment::combine`] must be associative, and [`Augment::identity`]
+/// must be its neutral element. The aggregate of a subtree is the combination, inorder,
+/// of the aggregates of its entries given by [`Augment::lift`]. It does not need to be
+/// commutative, as entries are always combined in the order of their intervals.
+///
+/// The unit type `()` is the trivial aggregate, maintained at no cost.
+/// It is the one maintained by default.
+///
+/// The aggregates are kept correct through insertions, removals and rotations.
+/// The values of a map maintaining an aggregate cannot be mutated in place, as the aggregates
+/// depending on them would not be updated: a value is replaced by inserting its interval again.
+///
+/// ```
+/// use std::ops::Bound;
+/// use unbounded_interval_tree::augmented_map::Augment;
+/// use unbounded_interval_tree::interval_map::IntervalMap;
+///
+/// // Number of intervals and total weight.
+/// #[derive(Clone, Debug, PartialEq)]
+/// struct Load {
+///     count: usize,
+///     total: u32,
+/// }
+///
+/// impl<K> Augment<K, u32> for Load {
+///     fn identity() -> Self {
+///         Load { count: 0, total: 0 }
+///     }
+///
+///     fn lift(_: &(Bound<K>, Bound<K>), weight: &u32) -> Self {
+///         Load { count: 1, total: *weight }
+///     }
+///
+///     fn combine(&self, other: &Self) -> Self {
+///         Load { count: self.count + other.count, total: self.total + other.total }
+///     }
+/// }
+///
+/// let mut map: IntervalMap<_, _, Load> = IntervalMap::new();
+///
+/// map.insert(0..10, 3);
+/// map.insert(5..15, 2);
+/// map.insert(20..30, 4);
+///
+/// assert_eq!(map.aggregate(), Load { count: 3, total: 9 });
+/// assert_eq!(map.aggregate_overlaps(&(12..25)), Load { count: 2, total: 6 });
+/// ```
+pub trait Augment<K, V> {
+    /// Returns the aggregate of no entries.
+    fn identity() -> Self;
+
+    /// Returns the aggregate of a single interval `range` associated with `value`.
+    fn lift(range: &Range<K>, value: &V) -> Self;
+
+    /// Returns the aggregate of the entries aggregated in `self`,
+    /// followed by the entries aggregated in `other`.
+    fn combine(&self, other: &Self) -> Self;
+}
+
+impl<K, V> Augment<K, V> for () {
+    fn identity() -> Self {}
+
+    fn lift(_: &Range<K>, _: &V) -> Self {}
+
+    fn combine(&self, _: &Self) -> Self {}
+}
+
+impl<K, V, A> IntervalMap<K, V, A> {
+    /// Returns the aggregate of all the entries of the map, in constant time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::ops::Bound;
+    /// use unbounded_interval_tree::augmented_map::Augment;
+    /// use unbounded_interval_tree::interval_map::IntervalMap;
+    ///
+    /// // Length of the longest interval.
+    /// #[derive(Clone, Debug, PartialEq)]
+    /// struct MaxLength(i32);
+    ///
+    /// impl<V> Augment<i32, V> for MaxLength {
+    ///     fn identity() -> Self {
+    ///         MaxLength(0)
+    ///     }
+    ///
+    ///     fn lift(range: &(Bound<i32>, Bound<i32>), _: &V) -> Self {
+    ///         match range {
+    ///             (Bound::Included(start), Bound::Excluded(end)) => MaxLength(end - start),
+    ///             _ => MaxLength(i32::MAX),
+    ///         }
+    ///     }
+    ///
+    ///     fn combine(&self, other: &Self) -> Self {
+    ///         MaxLength(self.0.max(other.0))
+    ///     }
+    /// }
+    ///
+    /// let mut map: IntervalMap<_, _, MaxLength> = IntervalMap::new();
+    /// assert_eq!(map.aggregate(), MaxLength(0));
+    ///
+    /// map.insert(0..10, ());
+    /// map.insert(5..8, ());
+    /// assert_eq!(map.aggregate(), MaxLength(10));
+    ///
+    /// map.remove(&(0..10));
+    /// assert_eq!(map.aggregate(), MaxLength(3));
+    /// ```
+    pub fn aggregate(&self) -> A
+    where
+        A: Augment<K, V> + Clone,
+    {
+        match self.root {
+            Some(ref root) => root.aggregate.clone(),
+            None => A::identity(),
+        }
+    }
+
+    /// Returns the aggregate of the entries of the map whose interval starts in the given `range`,
+    /// combined inorder. See [`IntervalTree::starting_in`] for the semantics of the start bounds.
+    ///
+    /// As the map is ordered by start bound, these entries are contiguous, so the aggregates
+    /// of whole subtrees are combined along the paths to both ends of `range`, in O(log n).
+    /// If `range` is empty or inverted, the identity is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::ops::Bound;
+    /// use unbounded_interval_tree::augmented_map::Augment;
+    /// use unbounded_interval_tree::interval_map::IntervalMap;
+    ///
+    /// // Total weight.
+    /// #[derive(Clone, Debug, PartialEq)]
+    /// struct Total(u32);
+    ///
+    /// impl<K> Augment<K, u32> for Total {
+    ///     fn identity() -> Self {
+    ///         Total(0)
+    ///     }
+    ///
+    ///     fn lift(_: &(Bound<K>, Bound<K>), weight: &u32) -> Self {
+    ///         Total(*weight)
+    ///     }
+    ///
+    ///     fn combine(&self, other: &Self) -> Self {
+    ///         Total(self.0 + other.0)
+    ///     }
+    /// }
+    ///
+    /// let map: IntervalMap<_, _, Total> =
+    ///     [(0..10, 1), (5..15, 2), (10..20, 4), (20..30, 8)].into_iter().collect();
+    ///
+    /// assert_eq!(map.aggregate_starting_in(&(5..=10)), Total(6));
+    /// assert_eq!(map.aggregate_starting_in(&(..20)), Total(7));
+    /// assert_eq!(map.aggregate_starting_in(&(11..20)), Total(0));
+    /// ```
+    pub fn aggregate_starting_in<Q, R>(&self, range: &R) -> A
+    where
+        K: Ord + Borrow<Q>,
+        A: Augment<K, V> + Clone,
+        R: RangeBounds<Q>,
+        Q: Ord + ?Sized,
+    {
+        let start = Cut::from_start(range.start_bound());
+        let end = Cut::from_end(range.end_bound());
+
+        // No interval starts in an empty or inverted range.
+        if start >= end {
+            return A::identity();
+        }
+
+        Self::aggregate_starts(&self.root, Included(start), Excluded(end))
+    }
+
+    // Returns the aggregate of the entries of the subtree whose start cut lies within `starts`.
+    // Once a node starts within `starts`, its left (resp. right) subtree is only bounded
+    // by the lower (resp. upper) end of `starts`, so only two paths are walked down.
+    fn aggregate_starts<Q>(curr: &Option<Box<Node<K, V, A>>>, lower: Bound<Cut<&Q>>, upper: Bound<Cut<&Q>>) -> A
+    where
+        K: Ord + Borrow<Q>,
+        A: Augment<K, V> + Clone,
+        Q: Ord + ?Sized,
+    {
+        let node = match curr {
+            None => return A::identity(),
+            Some(node) => node,
+        };
+        if let (Unbounded, Unbounded) = (lower, upper) {
+            return node.aggregate.clone();
+        }
+
+        let start = Cut::from_start(node.key.0.as_ref().map(Borrow::borrow));
+        let late_enough = match lower {
+            Included(min) => min <= start,
+            Excluded(min) => min < start,
+            Unbounded => true,
+        };
+        let early_enough = match upper {
+            Included(max) => start <= max,
+            Excluded(max) => start < max,
+            Unbounded => true,
+        };
+
+        match (late_enough, early_enough) {
+            // The left subtree starts before this node, so it starts too early as well.
+            (false, _) => Self::aggregate_starts(&node.right, lower, upper),
+            // The right subtree starts after this node, so it starts too late as well.
+            (_, false) => Self::aggregate_starts(&node.left, lower, upper),
+            (true, true) => Self::aggregate_starts(&node.left, lower, Unbounded)
+                .combine(&A::lift(&node.key, &node.data))
+                .combine(&Self::aggregate_starts(&node.right, Unbounded, upper)),
+        }
+    }
+
+    /// Returns the aggregate of the entries of the map whose interval overlaps
+    /// with the given `range` (partially or completely), combined inorder.
+    ///
+    /// Unlike [`IntervalMap::aggregate_starting_in`], this query is not logarithmic.
+    /// The entries starting in `range` are aggregated in O(log n), but the entries starting
+    /// before `range` and overlapping it do not form whole subtrees, so they are lifted
+    /// and combined one by one: the cost is linear in their number.
+    /// If `range` is empty or inverted, the identity is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::ops::Bound;
+    /// use unbounded_interval_tree::augmented_map::Augment;
+    /// use unbounded_interval_tree::interval_map::IntervalMap;
+    ///
+    /// // Highest priority.
+    /// #[derive(Clone, Debug, PartialEq)]
+    /// struct MaxPriority(Option<u8>);
+    ///
+    /// impl<K> Augment<K, u8> for MaxPriority {
+    ///     fn identity() -> Self {
+    ///         MaxPriority(None)
+    ///     }
+    ///
+    ///     fn lift(_: &(Bound<K>, Bound<K>), priority: &u8) -> Self {
+    ///         MaxPriority(Some(*priority))
+    ///     }
+    ///
+    ///     fn combine(&self, other: &Self) -> Self {
+    ///         MaxPriority(self.0.max(other.0))
+    ///     }
+    /// }
+    ///
+    /// let map: IntervalMap<_, _, MaxPriority> =
+    ///     [(0..10, 1), (5..15, 3), (20..30, 2)].into_iter().collect();
+    ///
+    /// assert_eq!(map.aggregate_overlaps(&(0..5)), MaxPriority(Some(1)));
+    /// assert_eq!(map.aggregate_overlaps(&(8..25)), MaxPriority(Some(3)));
+    /// assert_eq!(map.aggregate_overlaps(&(15..20)), MaxPriority(None));
+    /// ```
+    pub fn aggregate_overlaps<Q, R>(&self, range: &R) -> A
+    where
+        K: Ord + Borrow<Q>,
+        A: Augment<K, V> + Clone,
+        R: RangeBounds<Q>,
+        Q: Ord + ?Sized,
+    {
+        let start = Cut::from_start(range.start_bound());
+        let end = Cut::from_end(range.end_bound());
+
+        // No interval overlaps with an empty or inverted range.
+        if start >= end {
+            return A::identity();
+        }
+
+        // The overlapping entries starting before `range` come first inorder.
+        let before = OverlapIter::new(&self.root, range.start_bound(), range.end_bound())
+            .take_while(|node| Cut::from_start(node.key.0.as_ref().map(Borrow::borrow)) < start)
+            .fold(A::identity(), |aggregate, node| aggregate.combine(&A::lift(&node.key, &node.data)));
+
+        before.combine(&Self::aggregate_starts(&self.root, Included(start), Excluded(end)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{Value, from_str, to_string};
+    use std::cell::Cell;
+
+    // The inorder list of entries, which is associative but not commutative.
+    #[derive(Clone, Debug, PartialEq)]
+    struct Entries(Vec<(Range<i32>, char)>);
+
+    impl Augment<i32, char> for Entries {
+        fn identity() -> Self {
+            Entries(vec![])
+        }
+
+        fn lift(range: &Range<i32>, value: &char) -> Self {
+            Entries(vec![(*range, *value)])
+        }
+
+        fn combine(&self, other: &Self) -> Self {
+            Entries([&self.0[..], &other.0[..]].concat())
+        }
+    }
+
+    fn assert_aggregates_rec(node: &Option<Box<Node<i32, char, Entries>>>) -> Vec<(Range<i32>, char)> {
+        let node = match node {
+            None => return vec![],
+            Some(node) => node,
+        };
+
+        let mut entries = assert_aggregates_rec(&node.left);
+        entries.push((node.key, node.data));
+        entries.extend(assert_aggregates_rec(&node.right));
+        assert_eq!(node.aggregate.0, entries);
+
+        entries
+    }
+
+    #[test]
+    fn aggregate_starting_in_combines_subtree_aggregates() {
+        thread_local! {
+            static LIFTS: Cell<usize> = const { Cell::new(0) };
+        }
+
+        // Number of intervals, counting how many entries are lifted.
+        #[derive(Clone, Debug, PartialEq)]
+        struct Count(usize);
+
+        impl Augment<i32, ()> for Count {
+            fn identity() -> Self {
+                Count(0)
+            }
+
+            fn lift(_: &Range<i32>, _: &()) -> Self {
+                LIFTS.with(|lifts| lifts.set(lifts.get() + 1));
+                Count(1)
+            }
+
+            fn combine(&self, other: &Self) -> Self {
+                Count(self.0 + other.0)
+            }
+        }
+
+        let map: IntervalMap<_, _, Count> = (0..1024).map(|i| (i..i + 100, ())).collect();
+
+        // Only the entries along the paths to both ends of the range are lifted.
+        LIFTS.with(|lifts| lifts.set(0));
+        assert_eq!(map.aggregate_starting_in(&(100..900)), Count(800));
+        assert!(LIFTS.with(Cell::get) <= 2 * (map.root.as_ref().unwrap().height + 1));
+
+        // The entries starting before the range are lifted one by one.
+        LIFTS.with(|lifts| lifts.set(0));
+        assert_eq!(map.aggregate_overlaps(&(500..900)), Count(499));
+        assert!(LIFTS.with(Cell::get) <= 99 + 2 * (map.root.as_ref().unwrap().height + 1));
+
+        assert_eq!(map.aggregate_starting_in(&(Excluded(5), Excluded(5))), Count(0));
+        assert_eq!(map.aggregate_overlaps(&(Excluded(5), Excluded(5))), Count(0));
+    }
+
+    #[test]
+    fn serialize_deserialize_identity() {
+        let mut map: IntervalMap<_, _, Entries> = IntervalMap::new();
+        let serialized_empty_map = to_string(&map).unwrap();
+        let deserialized_empty_map = from_str(&serialized_empty_map).unwrap();
+        assert_eq!(map, deserialized_empty_map);
+
+        map.insert((Included(1), Excluded(3)), 'a');
+        map.insert((Excluded(2), Unbounded), 'b');
+        map.insert((Unbounded, Included(0)), 'c');
+        let serialized_map = to_string(&map).unwrap();
+        let deserialized_map: IntervalMap<_, _, Entries> = from_str(&serialized_map).unwrap();
+        assert_eq!(map, deserialized_map);
+
+        // Aggregates are recomputed rather than serialized.
+        assert_aggregates_rec(&deserialized_map.root);
+        assert_eq!(deserialized_map.aggregate(), map.aggregate());
+
+        // So is the size.
+        let serialized_map = serialized_map.replace(r#""size":3"#, r#""size":42"#);
+        let deserialized_map: IntervalMap<_, _, Entries> = from_str(&serialized_map).unwrap();
+        assert_eq!(deserialized_map.len(), 3);
+
+        // Intervals must be sorted.
+        let mut value: Value = from_str(&serialized_map).unwrap();
+        let root = &mut value["root"];
+        let left_key = root["left"]["key"].take();
+        root["left"]["key"] = root["right"]["key"].take();
+        root["right"]["key"] = left_key;
+        let err = from_str::<IntervalMap<i32, char, Entries>>(&value.to_string()).unwrap_err();
+        assert!(err.to_string().contains("the intervals are not sorted"));
+    }
+
+    #[test]
+    fn aggregates_are_maintained() {
+        let mut map = IntervalMap::new();
+
+        for i in 0..200 {
+            let start = (i * 37) % 101;
+            let range = (Included(start), Excluded(start + 1 + (i * 13) % 7));
+            let value = char::from(b'a' + (i % 26) as u8);
+
+            if i % 3 == 2 {
+                map.remove(&range);
+            } else {
+                map.insert(range, value);
+            }
+
+            let entries = assert_aggregates_rec(&map.root);
+            assert_eq!(map.aggregate().0, entries);
+
+            for query in [
+                (Included(i % 97), Included(i % 97 + 5)),
+                (Excluded(i % 97), Excluded(i % 97 + 9)),
+                (Unbounded, Excluded(i % 97)),
+                (Excluded(i % 97), Unbounded),
+            ] {
+                let overlaps = map.overlaps(&query).map(|(range, value)| (*range, *value)).collect::<Vec<_>>();
+                assert_eq!(map.aggregate_overlaps(&query).0, overlaps);
+
+                let starting_in = entries
+                    .iter()
+                    .filter(|(range, _)| {
+                        let start = Cut::from_start(range.0);
+                        Cut::from_start(query.0) <= start && start < Cut::from_end(query.1)
+                    })
+                    .cloned()
+                    .collect::<Vec<_>>();
+                assert_eq!(map.aggregate_starting_in(&query).0, starting_in);
+            }
+        }
+
+        map.clear();
+        assert_eq!(map.aggregate(), Entries(vec![]));
+    }
+}
@@ -0,0 +1,983 @@
+use crate::augmented_map::Augment;
+use crate::interval_tree::{Gaps, IntervalError, IntervalTree, OverlapIter, OverlapIterMut};
+use crate::node::{Node, NodeIter, NodeIterMut, Range};
+
+use std::borrow::Borrow;
+use std::cmp::Ordering::*;
+use std::fmt;
+use std::ops::Bound::*;
+use std::ops::RangeBounds;
+#[cfg(any(feature="serde", test))]
+use crate::node::SerializedTree;
+#[cfg(any(feature="serde", test))]
+use serde::{Serialize, Deserialize};
+
+/// An interval map, associating a value to every interval stored.
+/// It is built on the same data structure as [`IntervalTree`], and offers the same queries,
+/// which also give access to the values associated with the intervals.
+///
+/// The map can also maintain a custom aggregate `A` over every subtree, such as the total weight
+/// of its entries: see [`Augment`]. By default, the trivial aggregate `()` is maintained.
+///
+/// There are three ways to create an interval map.
+/// ```
+/// use unbounded_interval_tree::interval_map::IntervalMap;
+///
+/// // 1. Create an empty default interval map.
+/// let mut interval_map = IntervalMap::default();
+/// assert!(interval_map.is_empty());
+/// interval_map.insert(0..9, "first");
+/// interval_map.insert(27.., "second");
+/// assert_eq!(interval_map.len(), 2);
+///
+/// // 2. Create an interval map from an iterator.
+/// let entries = vec![("hello"..="hi", 1), ("Allo"..="Bonjour", 2)];
+/// let interval_map = entries.into_iter().collect::<IntervalMap<_, _>>();
+/// assert_eq!(interval_map.len(), 2);
+///
+/// // 3. Create an interval map from an array.
+/// let entries = [((1, 5)..(1, 9), 'a'), ((2, 3)..(3, 7), 'b')];
+/// let interval_map = IntervalMap::from(entries);
+/// assert_eq!(interval_map.len(), 2);
+/// ```
+#[cfg_attr(any(feature="serde", test), derive(Serialize, Deserialize))]
+#[cfg_attr(
+    any(feature="serde", test),
+    serde(
+        try_from = "SerializedTree<K, V, A>",
+        bound(
+            serialize = "K: Serialize, V: Serialize",
+            deserialize = "K: Deserialize<'de> + Ord + Clone, V: Deserialize<'de>, A: Augment<K, V>"
+        )
+    )
+)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct IntervalMap<K, V, A = ()> {
+    pub(crate) root: Option<Box<Node<K, V, A>>>,
+    size: usize,
+}
+
+impl<K, V, A> fmt::Display for IntervalMap<K, V, A>
+where
+    K: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.root {
+            Some(ref root) => write!(f, "{}", root),
+            None => write!(f, "Empty map"),
+        }
+    }
+}
+
+impl<K, V> Default for IntervalMap<K, V> {
+    fn default() -> IntervalMap<K, V> {
+        IntervalMap::new()
+    }
+}
+
+impl<K, V, A> Drop for IntervalMap<K, V, A> {
+    fn drop(&mut self) {
+        Node::drop_tree(self.root.take());
+    }
+}
+
+#[cfg(any(feature="serde", test))]
+impl<K, V, A> TryFrom<SerializedTree<K, V, A>> for IntervalMap<K, V, A>
+where
+    K: Ord,
+{
+    type Error = serde::de::value::Error;
+
+    fn try_from(tree: SerializedTree<K, V, A>) -> Result<Self, Self::Error> {
+        let size = tree.size()?;
+        Ok(IntervalMap {
+            root: tree.root,
+            size,
+        })
+    }
+}
+
+/// Creates an [`IntervalMap`] from an iterator of pairs of elements
+/// satisfying the [`RangeBounds`] trait and their associated values.
+impl<K, V, A, R> FromIterator<(R, V)> for IntervalMap<K, V, A>
+where
+    K: Ord + Clone,
+    A: Augment<K, V>,
+    R: RangeBounds<K>,
+{
+    fn from_iter<T: IntoIterator<Item = (R, V)>>(iter: T) -> Self {
+        let mut interval_map = Self::new();
+
+        for (interval, value) in iter {
+            interval_map.insert(interval, value);
+        }
+
+        interval_map
+    }
+}
+
+impl<K, V, R, const N: usize> From<[(R, V); N]> for IntervalMap<K, V>
+where
+    K: Ord + Clone,
+    R: RangeBounds<K>,
+{
+    fn from(entries: [(R, V); N]) -> Self {
+        let mut interval_map = Self::default();
+
+        for (interval, value) in entries {
+            interval_map.insert(interval, value);
+        }
+
+        interval_map
+    }
+}
+
+impl<K, V, A> IntervalMap<K, V, A> {
+    /// Creates an empty interval map, maintaining the aggregate `A` over its subtrees.
+    /// See [`Augment`] for more details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unbounded_interval_tree::interval_map::IntervalMap;
+    ///
+    /// let map: IntervalMap<i32, char> = IntervalMap::new();
+    ///
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn new() -> IntervalMap<K, V, A> {
+        IntervalMap {
+            root: None,
+            size: 0,
+        }
+    }
+
+    /// Produces an inorder iterator for the interval map, yielding
+    /// the intervals along with their associated values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::ops::Bound::Included;
+    /// use unbounded_interval_tree::interval_map::IntervalMap;
+    ///
+    /// let mut map = IntervalMap::default();
+    ///
+    /// map.insert((Included(0), Included(10)), 'a');
+    /// map.insert((Included(-5), Included(-1)), 'b');
+    ///
+    /// let mut iter = map.iter();
+    /// assert_eq!(iter.next(), Some((&(Included(-5), Included(-1)), &'b')));
+    /// assert_eq!(iter.next(), Some((&(Included(0), Included(10)), &'a')));
+    /// assert_eq!(iter.next(), None);
+    /// ```
+    pub fn iter<'a>(&'a self) -> IntervalMapIter<'a, K, V, A> {
+        IntervalMapIter {
+            inner: NodeIter::new(&self.root),
+        }
+    }
+
+    /// Inserts an interval `range` associated with `value` into the interval map.
+    /// It is ok to insert a `range` that overlaps with an existing interval in the map.
+    ///
+    /// If the map already stores an interval equal to `range`, its value is replaced,
+    /// and the previous value is returned. Otherwise, `None` is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::ops::Bound::{Included, Excluded};
+    /// use unbounded_interval_tree::interval_map::IntervalMap;
+    ///
+    /// let mut map = IntervalMap::default();
+    ///
+    /// assert_eq!(map.insert((Included(5), Excluded(9)), "first"), None);
+    /// assert_eq!(map.insert(..=10, "second"), None);
+    /// assert_eq!(map.insert(5..9, "third"), Some("first"));
+    /// assert_eq!(map.len(), 2);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is inverted or empty, like [`IntervalTree::insert`].
+    /// See [`IntervalMap::try_insert`] to handle invalid intervals instead.
+    pub fn insert<R>(&mut self, range: R, value: V) -> Option<V>
+    where
+        K: Ord + Clone,
+        A: Augment<K, V>,
+        R: RangeBounds<K>,
+    {
+        match self.try_insert(range, value) {
+            Ok(replaced) => replaced,
+            Err(err) => panic!("cannot insert an invalid interval: {}", err),
+        }
+    }
+
+    /// Inserts an interval `range` associated with `value` into the interval map,
+    /// like [`IntervalMap::insert`], unless `range` is invalid.
+    /// See [`IntervalTree::try_insert`] for more details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unbounded_interval_tree::interval_map::IntervalMap;
+    /// use unbounded_interval_tree::interval_tree::IntervalError;
+    ///
+    /// let mut map = IntervalMap::default();
+    ///
+    /// assert_eq!(map.try_insert(5..9, "first"), Ok(None));
+    /// assert_eq!(map.try_insert(5..9, "second"), Ok(Some("first")));
+    /// assert_eq!(map.try_insert(9..5, "third"), Err(IntervalError::Inverted));
+    /// assert_eq!(map.len(), 1);
+    /// ```
+    pub fn try_insert<R>(&mut self, range: R, value: V) -> Result<Option<V>, IntervalError>
+    where
+        K: Ord + Clone,
+        A: Augment<K, V>,
+        R: RangeBounds<K>,
+    {
+        let range = (range.start_bound().cloned(), range.end_bound().cloned());
+        IntervalTree::validate(&range)?;
+
+        let replaced = IntervalTree::insert_rec(&mut self.root, range, value);
+        if replaced.is_none() {
+            self.size += 1;
+        }
+
+        Ok(replaced)
+    }
+
+    /// Returns a reference to the value associated with the interval `range`,
+    /// if it is stored in the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unbounded_interval_tree::interval_map::IntervalMap;
+    ///
+    /// let map = IntervalMap::from([(0..10, 'a'), (5..15, 'b')]);
+    ///
+    /// assert_eq!(map.get(&(5..15)), Some(&'b'));
+    /// assert_eq!(map.get(&(5..10)), None);
+    /// ```
+    pub fn get<R>(&self, range: &R) -> Option<&V>
+    where
+        K: Ord + Clone,
+        R: RangeBounds<K>,
+    {
+        let range = (range.start_bound().cloned(), range.end_bound().cloned());
+
+        let mut curr = &self.root;
+        while let Some(node) = curr {
+            curr = match IntervalTree::cmp(&node.key, &range) {
+                Equal => return Some(&node.data),
+                Less => &node.right,
+                Greater => &node.left,
+            };
+        }
+
+        None
+    }
+
+    /// A "stabbing query" in the jargon: returns whether or not a point `p`
+    /// is contained in any of the intervals stored in the map.
+    ///
+    /// The given point may be of a borrowed form of the stored type `K`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::ops::Bound::{Excluded, Unbounded};
+    /// use unbounded_interval_tree::interval_map::IntervalMap;
+    ///
+    /// let mut map = IntervalMap::default();
+    ///
+    /// map.insert((Excluded(5), Unbounded), ());
+    ///
+    /// assert!(map.contains_point(&100));
+    /// assert!(!map.contains_point(&5));
+    /// ```
+    pub fn contains_point<Q>(&self, p: &Q) -> bool
+    where
+        K: Ord + Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.get_point_overlaps(p).next().is_some()
+    }
+
+    /// Returns an inorder iterator over all the intervals stored in the map that contain
+    /// the point `p`, along with their associated values.
+    ///
+    /// The given point may be of a borrowed form of the stored type `K`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::ops::Bound::{Included, Excluded, Unbounded};
+    /// use unbounded_interval_tree::interval_map::IntervalMap;
+    ///
+    /// let mut rules = IntervalMap::default();
+    ///
+    /// rules.insert((Included(0), Excluded(10)), "warmup");
+    /// rules.insert((Included(5), Unbounded), "peak");
+    ///
+    /// assert_eq!(rules.get_point_overlaps(&7).map(|(_, rule)| *rule).collect::<Vec<_>>(),
+    ///            vec!["warmup", "peak"]);
+    /// assert_eq!(rules.get_point_overlaps(&-1).next(), None);
+    /// ```
+    pub fn get_point_overlaps<'a, Q>(&'a self, p: &'a Q) -> impl Iterator<Item = (&'a Range<K>, &'a V)>
+    where
+        K: Ord + Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        OverlapIter::new(&self.root, Included(p), Included(p)).map(|node| (&node.key, &node.data))
+    }
+
+    /// An alternative "stabbing query": returns whether or not an interval `range`
+    /// is fully covered by the intervals stored in the map.
+    ///
+    /// The given `range` may have bounds that are of a borrowed form of the stored type `K`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::ops::Bound::{Included, Excluded};
+    /// use unbounded_interval_tree::interval_map::IntervalMap;
+    ///
+    /// let mut map = IntervalMap::default();
+    ///
+    /// map.insert((Included(20), Included(30)), 'a');
+    /// map.insert((Excluded(30), Excluded(50)), 'b');
+    ///
+    /// assert!(map.contains_interval(&(20..=40)));
+    /// assert!(!map.contains_interval(&(&30..=&50)));
+    /// ```
+    pub fn contains_interval<Q, R>(&self, range: &R) -> bool
+    where
+        K: Ord + Borrow<Q>,
+        R: RangeBounds<Q>,
+        Q: Ord + ?Sized,
+    {
+        IntervalTree::covers(&self.root, range)
+    }
+
+    /// Returns an inorder iterator over all the intervals stored in the map that overlap
+    /// with the given `range` (partially or completely), along with their associated values.
+    /// See [`IntervalTree::overlaps`] for more details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::ops::Bound::{Included, Excluded};
+    /// use unbounded_interval_tree::interval_map::IntervalMap;
+    ///
+    /// let map = IntervalMap::from([(0..5, 'a'), (3..9, 'b'), (7..10, 'c')]);
+    ///
+    /// let mut overlaps = map.overlaps(&(4..8));
+    /// assert_eq!(overlaps.next(), Some((&(Included(0), Excluded(5)), &'a')));
+    /// assert_eq!(overlaps.next(), Some((&(Included(3), Excluded(9)), &'b')));
+    /// ```
+    pub fn overlaps<'a, Q, R>(&'a self, range: &'a R) -> impl Iterator<Item = (&'a Range<K>, &'a V)>
+    where
+        K: Ord + Borrow<Q>,
+        R: RangeBounds<Q>,
+        Q: Ord + ?Sized + 'a,
+    {
+        OverlapIter::new(&self.root, range.start_bound(), range.end_bound())
+            .map(|node| (&node.key, &node.data))
+    }
+
+    /// Returns the inorder list of all intervals stored in the map that overlaps
+    /// with the given `range` (partially or completely), along with their associated values.
+    ///
+    /// The given `range` may have bounds that are of a borrowed form of the stored type `K`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::ops::Bound::{Included, Excluded};
+    /// use unbounded_interval_tree::interval_map::IntervalMap;
+    ///
+    /// let mut map = IntervalMap::default();
+    ///
+    /// map.insert((Included(0), Included(5)), 'a');
+    /// map.insert((Included(7), Excluded(10)), 'b');
+    ///
+    /// assert_eq!(map.get_interval_overlaps(&(-5..7)),
+    ///            vec![(&(Included(0), Included(5)), &'a')]);
+    /// assert!(map.get_interval_overlaps(&(&10..)).is_empty());
+    /// ```
+    pub fn get_interval_overlaps<Q, R>(&self, range: &R) -> Vec<(&Range<K>, &V)>
+    where
+        K: Ord + Borrow<Q>,
+        R: RangeBounds<Q>,
+        Q: Ord + ?Sized,
+    {
+        OverlapIter::new(&self.root, range.start_bound(), range.end_bound())
+            .map(|node| (&node.key, &node.data))
+            .collect()
+    }
+
+    /// Returns the ordered list of subintervals in `range` that are not covered by the map.
+    /// See [`IntervalTree::get_interval_difference`] for more details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::ops::Bound::{Included, Excluded, Unbounded};
+    /// use unbounded_interval_tree::interval_map::IntervalMap;
+    ///
+    /// let mut map = IntervalMap::default();
+    ///
+    /// map.insert((Included(0), Excluded(10)), 'a');
+    /// map.insert((Excluded(10), Included(30)), 'b');
+    ///
+    /// assert_eq!(map.get_interval_difference(&(-5..=30)),
+    ///            vec![(Included(&-5), Excluded(&0)),
+    ///                 (Included(&10), Included(&10))]);
+    /// ```
+    pub fn get_interval_difference<'a, Q, R>(&'a self, range: &'a R) -> Vec<Range<&'a Q>>
+    where
+        K: Ord + Borrow<Q>,
+        R: RangeBounds<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.gaps(range).collect()
+    }
+
+    /// Returns an iterator over the ordered subintervals in `range` that are not covered by the map.
+    /// See [`IntervalTree::gaps`] for more details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::ops::Bound::{Included, Excluded};
+    /// use unbounded_interval_tree::interval_map::IntervalMap;
+    ///
+    /// let map = IntervalMap::from([(0..10, 'a'), (12..20, 'b'), (25..30, 'c')]);
+    ///
+    /// assert_eq!(map.gaps(&(0..30)).next(), Some((Included(&10), Excluded(&12))));
+    /// ```
+    pub fn gaps<'a, Q, R>(&'a self, range: &'a R) -> impl Iterator<Item = Range<&'a Q>>
+    where
+        K: Ord + Borrow<Q>,
+        R: RangeBounds<Q>,
+        Q: Ord + ?Sized + 'a,
+    {
+        let (start, end) = (range.start_bound(), range.end_bound());
+        let overlaps = OverlapIter::new(&self.root, start, end).map(|node| &node.key);
+
+        Gaps::new(overlaps, start, end)
+    }
+
+    /// Removes the interval `range` from the map, and returns its associated value
+    /// if it was stored in the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unbounded_interval_tree::interval_map::IntervalMap;
+    ///
+    /// let mut map = IntervalMap::from([(5..9, 'a'), (0..10, 'b')]);
+    ///
+    /// assert_eq!(map.remove(&(5..9)), Some('a'));
+    /// assert_eq!(map.remove(&(5..9)), None);
+    /// assert_eq!(map.len(), 1);
+    /// ```
+    pub fn remove<R>(&mut self, range: &R) -> Option<V>
+    where
+        K: Ord + Clone,
+        A: Augment<K, V>,
+        R: RangeBounds<K>,
+    {
+        self.remove_entry(range).map(|(_, value)| value)
+    }
+
+    /// Removes the interval `range` from the map, and returns it along with its associated value
+    /// if it was stored in the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::ops::Bound::{Included, Excluded};
+    /// use unbounded_interval_tree::interval_map::IntervalMap;
+    ///
+    /// let mut map = IntervalMap::from([(5..9, 'a')]);
+    ///
+    /// assert_eq!(map.remove_entry(&(5..9)), Some(((Included(5), Excluded(9)), 'a')));
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn remove_entry<R>(&mut self, range: &R) -> Option<(Range<K>, V)>
+    where
+        K: Ord + Clone,
+        A: Augment<K, V>,
+        R: RangeBounds<K>,
+    {
+        let range = (range.start_bound().cloned(), range.end_bound().cloned());
+        let removed = IntervalTree::remove_rec(&mut self.root, &range)?;
+
+        self.size -= 1;
+        Some(removed)
+    }
+
+    /// Removes a random leaf from the map, and returns the range stored
+    /// in the said node along with its associated value.
+    ///
+    /// The returned value will be `None` if the map is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unbounded_interval_tree::interval_map::IntervalMap;
+    ///
+    /// let mut map = IntervalMap::from([(5..9, 'a')]);
+    ///
+    /// assert!(map.remove_random_leaf().is_some());
+    /// assert!(map.remove_random_leaf().is_none());
+    /// ```
+    pub fn remove_random_leaf(&mut self) -> Option<(Range<K>, V)>
+    where
+        K: Ord + Clone,
+        A: Augment<K, V>,
+    {
+        let leaf = IntervalTree::get_random_leaf(&self.root)?.clone();
+        self.remove_entry(&leaf)
+    }
+
+    /// Returns the number of ranges stored in the interval map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unbounded_interval_tree::interval_map::IntervalMap;
+    ///
+    /// let mut map = IntervalMap::default();
+    ///
+    /// assert_eq!(map.len(), 0);
+    ///
+    /// map.insert(5..9, 'a');
+    /// map.insert(..=10, 'b');
+    ///
+    /// assert_eq!(map.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.size
+    }
+
+    /// Returns `true` if the map contains no element.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unbounded_interval_tree::interval_map::IntervalMap;
+    ///
+    /// let mut map = IntervalMap::default();
+    ///
+    /// assert!(map.is_empty());
+    ///
+    /// map.insert(5..9, 'a');
+    ///
+    /// assert!(!map.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the height of the interval map, that is the number of nodes
+    /// on the longest path from the root to a leaf.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unbounded_interval_tree::interval_map::IntervalMap;
+    ///
+    /// let map: IntervalMap<_, _> = (0..100).map(|i| (i..i + 1, i)).collect();
+    ///
+    /// assert_eq!(map.height(), 7);
+    /// ```
+    pub fn height(&self) -> usize {
+        Node::height(&self.root)
+    }
+
+    /// Clear the interval map, removing all values stored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unbounded_interval_tree::interval_map::IntervalMap;
+    ///
+    /// let mut map = IntervalMap::default();
+    ///
+    /// map.insert(5.., 'a');
+    /// map.clear();
+    ///
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn clear(&mut self) {
+        Node::drop_tree(self.root.take());
+        self.size = 0;
+    }
+}
+
+// The values cannot be mutated in place when an aggregate is maintained,
+// as the aggregates depending on them would not be updated.
+impl<K, V> IntervalMap<K, V> {
+    /// Produces an inorder iterator for the interval map, yielding
+    /// the intervals along with a mutable reference to their associated values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unbounded_interval_tree::interval_map::IntervalMap;
+    ///
+    /// let mut map = IntervalMap::from([(0..10, 1), (-5..-1, 2)]);
+    ///
+    /// for (_, value) in map.iter_mut() {
+    ///     *value *= 10;
+    /// }
+    ///
+    /// assert_eq!(map.get(&(0..10)), Some(&10));
+    /// assert_eq!(map.get(&(-5..-1)), Some(&20));
+    /// ```
+    pub fn iter_mut<'a>(&'a mut self) -> IntervalMapIterMut<'a, K, V> {
+        IntervalMapIterMut {
+            inner: NodeIterMut::new(&mut self.root),
+        }
+    }
+
+    /// Returns a mutable reference to the value associated with the interval `range`,
+    /// if it is stored in the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unbounded_interval_tree::interval_map::IntervalMap;
+    ///
+    /// let mut map = IntervalMap::from([(0..10, 1)]);
+    ///
+    /// *map.get_mut(&(0..10)).unwrap() += 1;
+    /// assert_eq!(map.get(&(0..10)), Some(&2));
+    /// ```
+    pub fn get_mut<R>(&mut self, range: &R) -> Option<&mut V>
+    where
+        K: Ord + Clone,
+        R: RangeBounds<K>,
+    {
+        let range = (range.start_bound().cloned(), range.end_bound().cloned());
+
+        let mut curr = &mut self.root;
+        while let Some(node) = curr {
+            curr = match IntervalTree::cmp(&node.key, &range) {
+                Equal => return Some(&mut node.data),
+                Less => &mut node.right,
+                Greater => &mut node.left,
+            };
+        }
+
+        None
+    }
+
+    /// Returns the inorder list of all intervals stored in the map that overlaps
+    /// with the given `range` (partially or completely), along with a mutable reference
+    /// to their associated values.
+    ///
+    /// The given `range` may have bounds that are of a borrowed form of the stored type `K`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unbounded_interval_tree::interval_map::IntervalMap;
+    ///
+    /// let mut map = IntervalMap::from([(0..5, 1), (7..10, 2), (12..15, 3)]);
+    ///
+    /// for (_, value) in map.get_interval_overlaps_mut(&(3..=7)) {
+    ///     *value = 0;
+    /// }
+    ///
+    /// assert_eq!(map.iter().map(|(_, value)| *value).collect::<Vec<_>>(), vec![0, 0, 3]);
+    /// ```
+    pub fn get_interval_overlaps_mut<Q, R>(&mut self, range: &R) -> Vec<(&Range<K>, &mut V)>
+    where
+        K: Ord + Borrow<Q>,
+        R: RangeBounds<Q>,
+        Q: Ord + ?Sized,
+    {
+        OverlapIterMut::new(&mut self.root, range.start_bound(), range.end_bound()).collect()
+    }
+}
+
+/// An inorder interator through the interval map.
+pub struct IntervalMapIter<'a, K, V, A = ()> {
+    inner: NodeIter<'a, K, V, A>,
+}
+
+impl<'a, K, V, A> Iterator for IntervalMapIter<'a, K, V, A> {
+    type Item = (&'a Range<K>, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|node| (&node.key, &node.data))
+    }
+}
+
+/// An inorder interator through the interval map, giving mutable access to the values.
+pub struct IntervalMapIterMut<'a, K, V> {
+    inner: NodeIterMut<'a, K, V>,
+}
+
+impl<'a, K, V> Iterator for IntervalMapIterMut<'a, K, V> {
+    type Item = (&'a Range<K>, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{Value, from_str, json, to_string};
+
+    #[test]
+    fn serialize_deserialize_identity() {
+        let mut map = IntervalMap::default();
+        let serialized_empty_map = to_string(&map).unwrap();
+        let deserialized_empty_map = from_str(&serialized_empty_map).unwrap();
+        assert_eq!(map, deserialized_empty_map);
+
+        map.insert((Included(1), Excluded(3)), String::from("a"));
+        map.insert((Included(2), Included(4)), String::from("b"));
+        map.insert((Included(3), Included(4)), String::from("c"));
+        let serialized_map = to_string(&map).unwrap();
+        let deserialized_map: IntervalMap<_, _> = from_str(&serialized_map).unwrap();
+        assert_eq!(map, deserialized_map);
+        assert_eq!(deserialized_map.height(), 2);
+    }
+
+    #[test]
+    fn serialize() {
+        let mut map = IntervalMap::default();
+
+        map.insert((Included(2), Included(4)), 'b');
+        map.insert((Included(1), Excluded(3)), 'a');
+
+        let serialized_map = to_string(&map).unwrap();
+        let deserialized_map: Value = from_str(&serialized_map).unwrap();
+        let expected_value = json!({
+            "root": {
+                "key": [
+                    {"Included": 2},
+                    {"Included": 4},
+                ],
+                "data": "b",
+                "left": {
+                    "key": [
+                        {"Included": 1},
+                        {"Excluded": 3},
+                    ],
+                    "data": "a",
+                    "left": null,
+                    "right": null,
+                    "value": {"Excluded": 3},
+                },
+                "right": null,
+                "value": {"Included": 4},
+            },
+            "size": 2,
+        });
+        assert_eq!(expected_value, deserialized_map);
+    }
+
+    #[test]
+    fn deserialize_unit_values() {
+        let mut expected_map = IntervalMap::default();
+        expected_map.insert((Included(2), Included(4)), ());
+
+        // Zero-sized values are omitted when serializing.
+        let value = json!({
+            "root": {
+                "key": [
+                    {"Included": 2},
+                    {"Included": 4},
+                ],
+                "left": null,
+                "right": null,
+                "value": {"Included": 4},
+            },
+            "size": 1,
+        });
+        let serialized_map: Value = from_str(&to_string(&expected_map).unwrap()).unwrap();
+        assert_eq!(serialized_map, value);
+
+        let deserialized_map: IntervalMap<_, ()> = from_str(&value.to_string()).unwrap();
+        assert_eq!(expected_map, deserialized_map);
+
+        // Other values are mandatory.
+        assert!(from_str::<IntervalMap<i32, i32>>(&value.to_string()).is_err());
+    }
+
+    #[test]
+    fn deserialize_restores_invariants() {
+        // The size is recomputed from the nodes.
+        let map = IntervalMap::from([(0..5, 'a'), (5..9, 'b'), (9..12, 'c')]);
+        let serialized_map = to_string(&map).unwrap().replace(r#""size":3"#, r#""size":42"#);
+        let deserialized_map: IntervalMap<i32, char> = from_str(&serialized_map).unwrap();
+        assert_eq!(deserialized_map.len(), 3);
+        assert_eq!(map, deserialized_map);
+
+        let node = |start, end, data, left, right| {
+            json!({
+                "key": [{"Included": start}, {"Excluded": end}],
+                "data": data,
+                "left": left,
+                "right": right,
+            })
+        };
+
+        // Intervals must be sorted.
+        let value = json!({
+            "root": node(0, 5, 'a', node(5, 9, 'b', Value::Null, Value::Null), Value::Null),
+            "size": 2,
+        });
+        let err = from_str::<IntervalMap<i32, char>>(&value.to_string()).unwrap_err();
+        assert!(err.to_string().contains("the intervals are not sorted"));
+
+        // The tree must be balanced.
+        let left = node(0, 5, 'a', Value::Null, Value::Null);
+        let value = json!({
+            "root": node(9, 12, 'c', node(5, 9, 'b', left, Value::Null), Value::Null),
+            "size": 3,
+        });
+        let err = from_str::<IntervalMap<i32, char>>(&value.to_string()).unwrap_err();
+        assert!(err.to_string().contains("the tree is unbalanced"));
+    }
+
+    #[test]
+    fn insert_replaces_value_of_identical_interval() {
+        let mut map = IntervalMap::default();
+
+        assert_eq!(map.insert(0..10, 'a'), None);
+        assert_eq!(map.insert(0..=10, 'b'), None);
+        assert_eq!(map.insert(0..10, 'c'), Some('a'));
+
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.get(&(0..10)), Some(&'c'));
+        assert_eq!(map.get(&(0..=10)), Some(&'b'));
+        assert_eq!(map.get(&(0..)), None);
+    }
+
+    #[test]
+    fn overlaps_works_as_expected() {
+        let root_key = (Included(2), Included(3));
+        let left_key = (Included(0), Included(1));
+        let left_left_key = (Included(-5), Excluded(10));
+        let right_key = (Excluded(3), Unbounded);
+
+        let mut map = IntervalMap::from([
+            (root_key, "root"),
+            (left_key, "left"),
+            (left_left_key, "left_left"),
+            (right_key, "right"),
+        ]);
+
+        assert_eq!(
+            map.get_interval_overlaps(&(3..10)),
+            vec![
+                (&left_left_key, &"left_left"),
+                (&root_key, &"root"),
+                (&right_key, &"right")
+            ]
+        );
+        assert_eq!(
+            map.get_interval_overlaps(&(Excluded(3), Excluded(10))),
+            vec![(&left_left_key, &"left_left"), (&right_key, &"right")]
+        );
+        assert!(map.get_interval_overlaps(&(..-5)).is_empty());
+
+        for (_, value) in map.get_interval_overlaps_mut(&(..=1)) {
+            *value = "updated";
+        }
+        assert_eq!(
+            map.iter().collect::<Vec<_>>(),
+            vec![
+                (&left_left_key, &"updated"),
+                (&left_key, &"updated"),
+                (&root_key, &"root"),
+                (&right_key, &"right")
+            ]
+        );
+    }
+
+    #[test]
+    fn point_overlaps_works_as_expected() {
+        let map = IntervalMap::from([
+            ((Included(10), Excluded(20)), 1),
+            ((Unbounded, Included(10)), 2),
+            ((Excluded(10), Unbounded), 3),
+        ]);
+
+        assert_eq!(
+            map.get_point_overlaps(&10).collect::<Vec<_>>(),
+            vec![
+                (&(Unbounded, Included(10)), &2),
+                (&(Included(10), Excluded(20)), &1)
+            ]
+        );
+        assert_eq!(
+            map.get_point_overlaps(&20).collect::<Vec<_>>(),
+            vec![(&(Excluded(10), Unbounded), &3)]
+        );
+    }
+
+    #[test]
+    fn difference_and_contains_works_as_expected() {
+        let map = IntervalMap::from([
+            ((Included(10), Excluded(20)), 1),
+            ((Excluded(30), Excluded(40)), 2),
+            ((Included(40), Unbounded), 3),
+        ]);
+
+        assert_eq!(
+            map.get_interval_difference(&(0..=40)),
+            vec![
+                (Included(&0), Excluded(&10)),
+                (Included(&20), Included(&30))
+            ]
+        );
+        assert!(map.contains_point(&10));
+        assert!(!map.contains_point(&20));
+        assert!(map.contains_interval(&(35..)));
+        assert!(!map.contains_interval(&(15..35)));
+    }
+
+    #[test]
+    fn iter_mut_works_as_expected() {
+        let mut map: IntervalMap<_, _> = (0..100).rev().map(|i| (i..i + 1, i)).collect();
+
+        for (range, value) in map.iter_mut() {
+            assert_eq!(range.0, Included(*value));
+            *value *= 2;
+        }
+
+        assert!(map.iter().map(|(_, value)| *value).eq((0..100).map(|i| i * 2)));
+    }
+
+    #[test]
+    fn remove_works_as_expected() {
+        let mut map: IntervalMap<_, _> = (0..100).map(|i| (i..i + 1, i)).collect();
+
+        for i in (0..100).step_by(3) {
+            assert_eq!(map.remove(&(i..i + 1)), Some(i));
+            assert_eq!(map.remove(&(i..i + 1)), None);
+        }
+        assert_eq!(map.len(), 66);
+        assert!(map.iter().all(|(range, value)| range.0 == Included(*value) && value % 3 != 0));
+
+        let mut removed = 0;
+        while let Some((range, value)) = map.remove_random_leaf() {
+            assert_eq!(range, (Included(value), Excluded(value + 1)));
+            removed += 1;
+        }
+        assert_eq!(removed, 66);
+        assert!(map.is_empty());
+    }
+}
@@ -0,0 +1,687 @@
+use crate::interval_map::{IntervalMap, IntervalMapIter};
+use crate::interval_tree::IntervalError;
+use crate::node::Range;
+
+use std::borrow::Borrow;
+use std::fmt;
+use std::mem;
+use std::ops::RangeBounds;
+use std::slice;
+#[cfg(any(feature="serde", test))]
+use serde::{Deserialize, Serialize, Serializer};
+
+/// An interval multimap, associating any number of values to every interval stored.
+/// Unlike [`IntervalMap`], inserting an interval identical to one already stored keeps
+/// both occurrences, each with its own value.
+///
+/// Using `()` as values turns the multimap into a multiset of intervals, where
+/// the number of values associated with an interval is its multiplicity.
+///
+/// ```
+/// use unbounded_interval_tree::interval_multimap::IntervalMultiMap;
+///
+/// let mut bookings = IntervalMultiMap::default();
+/// bookings.insert(9..12, "Alice");
+/// bookings.insert(9..12, "Bob");
+/// bookings.insert(13..17, "Alice");
+///
+/// assert_eq!(bookings.len(), 3);
+/// assert_eq!(bookings.get(&(9..12)), Some(&["Alice", "Bob"][..]));
+///
+/// // A multiset of intervals.
+/// let intervals = vec![0..5, 0..5, 3..9];
+/// let multiset = intervals.into_iter().map(|range| (range, ())).collect::<IntervalMultiMap<_, _>>();
+/// assert_eq!(multiset.count(&(0..5)), 2);
+/// ```
+#[cfg_attr(any(feature="serde", test), derive(Deserialize))]
+#[cfg_attr(
+    any(feature="serde", test),
+    serde(
+        from = "IntervalMap<K, Vec<V>>",
+        bound(deserialize = "K: Deserialize<'de> + Ord + Clone, V: Deserialize<'de>")
+    )
+)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct IntervalMultiMap<K, V> {
+    // Every interval is associated with a non-empty list of values.
+    map: IntervalMap<K, Vec<V>>,
+    size: usize,
+}
+
+// A multimap is serialized as the interval map of its lists of values, so that it is
+// deserialized through `From<IntervalMap<K, Vec<V>>>`, which restores its invariants.
+#[cfg(any(feature="serde", test))]
+impl<K, V> Serialize for IntervalMultiMap<K, V>
+where
+    K: Serialize,
+    V: Serialize,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.map.serialize(serializer)
+    }
+}
+
+impl<K, V> fmt::Display for IntervalMultiMap<K, V>
+where
+    K: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.map)
+    }
+}
+
+impl<K, V> Default for IntervalMultiMap<K, V> {
+    fn default() -> IntervalMultiMap<K, V> {
+        IntervalMultiMap {
+            map: IntervalMap::default(),
+            size: 0,
+        }
+    }
+}
+
+/// Creates an [`IntervalMultiMap`] from an iterator of pairs of elements
+/// satisfying the [`RangeBounds`] trait and their associated values.
+impl<K, V, R> FromIterator<(R, V)> for IntervalMultiMap<K, V>
+where
+    K: Ord + Clone,
+    R: RangeBounds<K>,
+{
+    fn from_iter<T: IntoIterator<Item = (R, V)>>(iter: T) -> Self {
+        let mut interval_multimap = Self::default();
+
+        for (interval, value) in iter {
+            interval_multimap.insert(interval, value);
+        }
+
+        interval_multimap
+    }
+}
+
+impl<K, V, R, const N: usize> From<[(R, V); N]> for IntervalMultiMap<K, V>
+where
+    K: Ord + Clone,
+    R: RangeBounds<K>,
+{
+    fn from(entries: [(R, V); N]) -> Self {
+        entries.into_iter().collect()
+    }
+}
+
+/// Creates an [`IntervalMultiMap`] from an [`IntervalMap`] associating a list of values
+/// to every interval. Intervals associated with an empty list are discarded.
+///
+/// # Examples
+///
+/// ```
+/// use std::ops::Bound::{Included, Excluded};
+/// use unbounded_interval_tree::interval_map::IntervalMap;
+/// use unbounded_interval_tree::interval_multimap::IntervalMultiMap;
+///
+/// let map = IntervalMap::from([(0..5, vec!['a', 'b']), (5..9, vec![])]);
+/// let multimap = IntervalMultiMap::from(map);
+///
+/// assert_eq!(multimap.len(), 2);
+/// assert_eq!(multimap.iter().collect::<Vec<_>>(), vec![
+///     (&(Included(0), Excluded(5)), &'a'),
+///     (&(Included(0), Excluded(5)), &'b'),
+/// ]);
+/// ```
+impl<K, V> From<IntervalMap<K, Vec<V>>> for IntervalMultiMap<K, V>
+where
+    K: Ord + Clone,
+{
+    fn from(mut map: IntervalMap<K, Vec<V>>) -> Self {
+        // The map is rebuilt rather than pruned, so that its own size is recomputed as well.
+        let map = map
+            .iter_mut()
+            .filter(|(_, values)| !values.is_empty())
+            .map(|(range, values)| (range.clone(), mem::take(values)))
+            .collect::<IntervalMap<_, _>>();
+
+        let size = map.iter().map(|(_, values)| values.len()).sum();
+        IntervalMultiMap { map, size }
+    }
+}
+
+impl<K, V> IntervalMultiMap<K, V> {
+    /// Produces an inorder iterator for the interval multimap, yielding every occurrence
+    /// of the intervals along with their associated values.
+    /// Values of identical intervals are yielded in their order of insertion.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::ops::Bound::{Included, Excluded};
+    /// use unbounded_interval_tree::interval_multimap::IntervalMultiMap;
+    ///
+    /// let multimap = IntervalMultiMap::from([(5..9, 'a'), (0..5, 'b'), (5..9, 'c')]);
+    ///
+    /// let mut iter = multimap.iter();
+    /// assert_eq!(iter.next(), Some((&(Included(0), Excluded(5)), &'b')));
+    /// assert_eq!(iter.next(), Some((&(Included(5), Excluded(9)), &'a')));
+    /// assert_eq!(iter.next(), Some((&(Included(5), Excluded(9)), &'c')));
+    /// assert_eq!(iter.next(), None);
+    /// ```
+    pub fn iter<'a>(&'a self) -> IntervalMultiMapIter<'a, K, V> {
+        IntervalMultiMapIter {
+            inner: self.map.iter(),
+            curr: None,
+        }
+    }
+
+    /// Inserts an occurrence of the interval `range` associated with `value`
+    /// into the interval multimap.
+    ///
+    /// Returns whether `range` was newly inserted, that is whether the multimap
+    /// did not already store an identical interval.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unbounded_interval_tree::interval_multimap::IntervalMultiMap;
+    ///
+    /// let mut multimap = IntervalMultiMap::default();
+    ///
+    /// assert!(multimap.insert(5..9, 'a'));
+    /// assert!(!multimap.insert(5..9, 'b'));
+    /// assert_eq!(multimap.len(), 2);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is inverted or empty, like
+    /// [`IntervalTree::insert`](crate::interval_tree::IntervalTree::insert).
+    /// See [`IntervalMultiMap::try_insert`] to handle invalid intervals instead.
+    pub fn insert<R>(&mut self, range: R, value: V) -> bool
+    where
+        K: Ord + Clone,
+        R: RangeBounds<K>,
+    {
+        match self.try_insert(range, value) {
+            Ok(inserted) => inserted,
+            Err(err) => panic!("cannot insert an invalid interval: {}", err),
+        }
+    }
+
+    /// Inserts an occurrence of the interval `range` associated with `value` into the
+    /// interval multimap, like [`IntervalMultiMap::insert`], unless `range` is invalid.
+    /// See [`IntervalTree::try_insert`](crate::interval_tree::IntervalTree::try_insert)
+    /// for more details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unbounded_interval_tree::interval_multimap::IntervalMultiMap;
+    /// use unbounded_interval_tree::interval_tree::IntervalError;
+    ///
+    /// let mut multimap = IntervalMultiMap::default();
+    ///
+    /// assert_eq!(multimap.try_insert(5..9, 'a'), Ok(true));
+    /// assert_eq!(multimap.try_insert(5..9, 'b'), Ok(false));
+    /// assert_eq!(multimap.try_insert(5..5, 'c'), Err(IntervalError::Empty));
+    /// assert_eq!(multimap.len(), 2);
+    /// ```
+    pub fn try_insert<R>(&mut self, range: R, value: V) -> Result<bool, IntervalError>
+    where
+        K: Ord + Clone,
+        R: RangeBounds<K>,
+    {
+        let inserted = match self.map.get_mut(&range) {
+            Some(values) => {
+                values.push(value);
+                false
+            }
+            None => {
+                self.map.try_insert(range, vec![value])?;
+                true
+            }
+        };
+
+        self.size += 1;
+        Ok(inserted)
+    }
+
+    /// Returns the values associated with the interval `range`, in their order of insertion,
+    /// if it is stored in the multimap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unbounded_interval_tree::interval_multimap::IntervalMultiMap;
+    ///
+    /// let multimap = IntervalMultiMap::from([(5..9, 'a'), (5..9, 'b')]);
+    ///
+    /// assert_eq!(multimap.get(&(5..9)), Some(&['a', 'b'][..]));
+    /// assert_eq!(multimap.get(&(5..=9)), None);
+    /// ```
+    pub fn get<R>(&self, range: &R) -> Option<&[V]>
+    where
+        K: Ord + Clone,
+        R: RangeBounds<K>,
+    {
+        self.map.get(range).map(Vec::as_slice)
+    }
+
+    /// Returns the number of occurrences of the interval `range` stored in the multimap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unbounded_interval_tree::interval_multimap::IntervalMultiMap;
+    ///
+    /// let multimap = IntervalMultiMap::from([(5..9, ()), (5..9, ()), (0..9, ())]);
+    ///
+    /// assert_eq!(multimap.count(&(5..9)), 2);
+    /// assert_eq!(multimap.count(&(0..9)), 1);
+    /// assert_eq!(multimap.count(&(0..5)), 0);
+    /// ```
+    pub fn count<R>(&self, range: &R) -> usize
+    where
+        K: Ord + Clone,
+        R: RangeBounds<K>,
+    {
+        self.map.get(range).map_or(0, Vec::len)
+    }
+
+    /// A "stabbing query" in the jargon: returns whether or not a point `p`
+    /// is contained in any of the intervals stored in the multimap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unbounded_interval_tree::interval_multimap::IntervalMultiMap;
+    ///
+    /// let multimap = IntervalMultiMap::from([(5..9, 'a'), (5..9, 'b')]);
+    ///
+    /// assert!(multimap.contains_point(&5));
+    /// assert!(!multimap.contains_point(&9));
+    /// ```
+    pub fn contains_point<Q>(&self, p: &Q) -> bool
+    where
+        K: Ord + Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.map.contains_point(p)
+    }
+
+    /// Returns an inorder iterator over every occurrence of the intervals stored in the multimap
+    /// that contain the point `p`, along with their associated values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unbounded_interval_tree::interval_multimap::IntervalMultiMap;
+    ///
+    /// let multimap = IntervalMultiMap::from([(5..9, 'a'), (0..6, 'b'), (5..9, 'c')]);
+    ///
+    /// assert_eq!(multimap.get_point_overlaps(&5).map(|(_, value)| *value).collect::<String>(),
+    ///            "bac");
+    /// ```
+    pub fn get_point_overlaps<'a, Q>(&'a self, p: &'a Q) -> impl Iterator<Item = (&'a Range<K>, &'a V)>
+    where
+        K: Ord + Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.map
+            .get_point_overlaps(p)
+            .flat_map(|(range, values)| values.iter().map(move |value| (range, value)))
+    }
+
+    /// An alternative "stabbing query": returns whether or not an interval `range`
+    /// is fully covered by the intervals stored in the multimap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unbounded_interval_tree::interval_multimap::IntervalMultiMap;
+    ///
+    /// let multimap = IntervalMultiMap::from([(5..9, 'a'), (9..12, 'b')]);
+    ///
+    /// assert!(multimap.contains_interval(&(5..12)));
+    /// assert!(!multimap.contains_interval(&(5..=12)));
+    /// ```
+    pub fn contains_interval<Q, R>(&self, range: &R) -> bool
+    where
+        K: Ord + Borrow<Q>,
+        R: RangeBounds<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.map.contains_interval(range)
+    }
+
+    /// Returns an inorder iterator over every occurrence of the intervals stored in the multimap
+    /// that overlaps with the given `range` (partially or completely), along with their
+    /// associated values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unbounded_interval_tree::interval_multimap::IntervalMultiMap;
+    ///
+    /// let multimap = IntervalMultiMap::from([(5..9, 'a'), (0..3, 'b'), (5..9, 'c')]);
+    ///
+    /// assert_eq!(multimap.overlaps(&(2..6)).map(|(_, value)| *value).collect::<String>(),
+    ///            "bac");
+    /// ```
+    pub fn overlaps<'a, Q, R>(&'a self, range: &'a R) -> impl Iterator<Item = (&'a Range<K>, &'a V)>
+    where
+        K: Ord + Borrow<Q>,
+        R: RangeBounds<Q>,
+        Q: Ord + ?Sized + 'a,
+    {
+        self.map
+            .overlaps(range)
+            .flat_map(|(range, values)| values.iter().map(move |value| (range, value)))
+    }
+
+    /// Returns the inorder list of every occurrence of the intervals stored in the multimap
+    /// that overlaps with the given `range` (partially or completely), along with their
+    /// associated values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::ops::Bound::{Included, Excluded};
+    /// use unbounded_interval_tree::interval_multimap::IntervalMultiMap;
+    ///
+    /// let multimap = IntervalMultiMap::from([(5..9, 'a'), (0..3, 'b'), (5..9, 'c')]);
+    ///
+    /// assert_eq!(multimap.get_interval_overlaps(&(4..6)),
+    ///            vec![(&(Included(5), Excluded(9)), &'a'),
+    ///                 (&(Included(5), Excluded(9)), &'c')]);
+    /// ```
+    pub fn get_interval_overlaps<Q, R>(&self, range: &R) -> Vec<(&Range<K>, &V)>
+    where
+        K: Ord + Borrow<Q>,
+        R: RangeBounds<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.map
+            .get_interval_overlaps(range)
+            .into_iter()
+            .flat_map(|(range, values)| values.iter().map(move |value| (range, value)))
+            .collect()
+    }
+
+    /// Returns the ordered list of subintervals in `range` that are not covered by the multimap.
+    /// See [`IntervalTree::get_interval_difference`](crate::interval_tree::IntervalTree::get_interval_difference)
+    /// for more details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::ops::Bound::{Included, Excluded};
+    /// use unbounded_interval_tree::interval_multimap::IntervalMultiMap;
+    ///
+    /// let multimap = IntervalMultiMap::from([(5..9, 'a'), (5..9, 'b')]);
+    ///
+    /// assert_eq!(multimap.get_interval_difference(&(0..10)),
+    ///            vec![(Included(&0), Excluded(&5)),
+    ///                 (Included(&9), Excluded(&10))]);
+    /// ```
+    pub fn get_interval_difference<'a, Q, R>(&'a self, range: &'a R) -> Vec<Range<&'a Q>>
+    where
+        K: Ord + Borrow<Q>,
+        R: RangeBounds<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.map.get_interval_difference(range)
+    }
+
+    /// Returns an iterator over the ordered subintervals in `range` that are not covered by the multimap.
+    /// See [`IntervalTree::gaps`](crate::interval_tree::IntervalTree::gaps) for more details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::ops::Bound::{Included, Excluded};
+    /// use unbounded_interval_tree::interval_multimap::IntervalMultiMap;
+    ///
+    /// let multimap = IntervalMultiMap::from([(5..9, 'a'), (5..9, 'b')]);
+    ///
+    /// assert_eq!(multimap.gaps(&(0..10)).next(), Some((Included(&0), Excluded(&5))));
+    /// ```
+    pub fn gaps<'a, Q, R>(&'a self, range: &'a R) -> impl Iterator<Item = Range<&'a Q>>
+    where
+        K: Ord + Borrow<Q>,
+        R: RangeBounds<Q>,
+        Q: Ord + ?Sized + 'a,
+    {
+        self.map.gaps(range)
+    }
+
+    /// Removes one occurrence of the interval `range` from the multimap, and returns
+    /// its associated value. The most recently inserted occurrence is removed first,
+    /// so other occurrences of `range` are left untouched.
+    ///
+    /// The returned value will be `None` if `range` is not stored in the multimap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unbounded_interval_tree::interval_multimap::IntervalMultiMap;
+    ///
+    /// let mut multimap = IntervalMultiMap::from([(5..9, 'a'), (5..9, 'b')]);
+    ///
+    /// assert_eq!(multimap.remove(&(5..9)), Some('b'));
+    /// assert_eq!(multimap.get(&(5..9)), Some(&['a'][..]));
+    /// assert_eq!(multimap.remove(&(5..9)), Some('a'));
+    /// assert_eq!(multimap.remove(&(5..9)), None);
+    /// assert!(multimap.is_empty());
+    /// ```
+    pub fn remove<R>(&mut self, range: &R) -> Option<V>
+    where
+        K: Ord + Clone,
+        R: RangeBounds<K>,
+    {
+        let values = self.map.get_mut(range)?;
+        let removed = values.pop();
+
+        if values.is_empty() {
+            self.map.remove(range);
+        }
+
+        self.size -= 1;
+        removed
+    }
+
+    /// Removes every occurrence of the interval `range` from the multimap, and returns
+    /// their associated values in their order of insertion.
+    ///
+    /// The returned value will be `None` if `range` is not stored in the multimap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unbounded_interval_tree::interval_multimap::IntervalMultiMap;
+    ///
+    /// let mut multimap = IntervalMultiMap::from([(5..9, 'a'), (0..9, 'b'), (5..9, 'c')]);
+    ///
+    /// assert_eq!(multimap.remove_all(&(5..9)), Some(vec!['a', 'c']));
+    /// assert_eq!(multimap.len(), 1);
+    /// ```
+    pub fn remove_all<R>(&mut self, range: &R) -> Option<Vec<V>>
+    where
+        K: Ord + Clone,
+        R: RangeBounds<K>,
+    {
+        let removed = self.map.remove(range)?;
+
+        self.size -= removed.len();
+        Some(removed)
+    }
+
+    /// Returns the number of occurrences of intervals stored in the multimap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unbounded_interval_tree::interval_multimap::IntervalMultiMap;
+    ///
+    /// let mut multimap = IntervalMultiMap::default();
+    ///
+    /// multimap.insert(5..9, 'a');
+    /// multimap.insert(5..9, 'b');
+    ///
+    /// assert_eq!(multimap.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.size
+    }
+
+    /// Returns `true` if the multimap contains no element.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unbounded_interval_tree::interval_multimap::IntervalMultiMap;
+    ///
+    /// let mut multimap = IntervalMultiMap::default();
+    ///
+    /// assert!(multimap.is_empty());
+    ///
+    /// multimap.insert(5..9, 'a');
+    ///
+    /// assert!(!multimap.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Clear the interval multimap, removing all values stored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unbounded_interval_tree::interval_multimap::IntervalMultiMap;
+    ///
+    /// let mut multimap = IntervalMultiMap::from([(5..9, 'a'), (5..9, 'b')]);
+    ///
+    /// multimap.clear();
+    ///
+    /// assert!(multimap.is_empty());
+    /// ```
+    pub fn clear(&mut self) {
+        self.map.clear();
+        self.size = 0;
+    }
+}
+
+/// An inorder interator through the interval multimap.
+pub struct IntervalMultiMapIter<'a, K, V> {
+    inner: IntervalMapIter<'a, K, Vec<V>>,
+    // Remaining values of the interval being visited.
+    curr: Option<(&'a Range<K>, slice::Iter<'a, V>)>,
+}
+
+impl<'a, K, V> Iterator for IntervalMultiMapIter<'a, K, V> {
+    type Item = (&'a Range<K>, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((range, values)) = &mut self.curr {
+                if let Some(value) = values.next() {
+                    return Some((range, value));
+                }
+            }
+
+            let (range, values) = self.inner.next()?;
+            self.curr = Some((range, values.iter()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{from_str, to_string};
+    use std::ops::Bound::*;
+
+    #[test]
+    fn serialize_deserialize_identity() {
+        let mut multimap = IntervalMultiMap::default();
+        let serialized_empty_multimap = to_string(&multimap).unwrap();
+        let deserialized_empty_multimap = from_str(&serialized_empty_multimap).unwrap();
+        assert_eq!(multimap, deserialized_empty_multimap);
+
+        multimap.insert((Included(1), Excluded(3)), 'a');
+        multimap.insert((Included(1), Excluded(3)), 'b');
+        multimap.insert((Included(2), Unbounded), 'c');
+        let serialized_multimap = to_string(&multimap).unwrap();
+        let deserialized_multimap: IntervalMultiMap<_, _> = from_str(&serialized_multimap).unwrap();
+        assert_eq!(multimap, deserialized_multimap);
+        assert_eq!(deserialized_multimap.len(), 3);
+    }
+
+    #[test]
+    fn deserialize_restores_invariants() {
+        // Empty lists of values are discarded, and the size is recomputed.
+        let map = IntervalMap::from([(0..5, vec!['a', 'b']), (5..9, vec![]), (9..12, vec!['c'])]);
+        let serialized_map = to_string(&map).unwrap().replace(r#""size":3"#, r#""size":42"#);
+        let mut multimap: IntervalMultiMap<i32, char> = from_str(&serialized_map).unwrap();
+
+        assert_eq!(multimap.len(), 3);
+        assert_eq!(multimap.get(&(5..9)), None);
+        assert_eq!(multimap.remove(&(5..9)), None);
+        assert_eq!(multimap.remove_all(&(0..5)), Some(vec!['a', 'b']));
+        assert_eq!(multimap.remove(&(9..12)), Some('c'));
+        assert!(multimap.is_empty());
+    }
+
+    #[test]
+    fn identical_intervals_are_kept() {
+        let mut multimap = IntervalMultiMap::default();
+
+        let key1 = (Included(10), Excluded(20));
+        let key2 = (Excluded(30), Excluded(40));
+
+        assert!(multimap.insert(key1, 1));
+        assert!(multimap.insert(key2, 2));
+        assert!(!multimap.insert(key1, 3));
+        assert!(!multimap.insert(key1, 4));
+
+        assert_eq!(multimap.len(), 4);
+        assert_eq!(multimap.count(&key1), 3);
+        assert_eq!(multimap.count(&key2), 1);
+        assert_eq!(
+            multimap.iter().collect::<Vec<_>>(),
+            vec![(&key1, &1), (&key1, &3), (&key1, &4), (&key2, &2)]
+        );
+        assert_eq!(
+            multimap.get_interval_overlaps(&(15..35)),
+            vec![(&key1, &1), (&key1, &3), (&key1, &4), (&key2, &2)]
+        );
+        assert_eq!(
+            multimap.get_interval_overlaps(&(..=10)),
+            vec![(&key1, &1), (&key1, &3), (&key1, &4)]
+        );
+    }
+
+    #[test]
+    fn remove_keeps_other_occurrences() {
+        let mut multimap = IntervalMultiMap::default();
+
+        let key1 = (Included(10), Excluded(20));
+        let key2 = (Excluded(30), Excluded(40));
+
+        multimap.insert(key1, ());
+        multimap.insert(key1, ());
+        multimap.insert(key2, ());
+
+        assert_eq!(multimap.remove(&key1), Some(()));
+        assert_eq!(multimap.len(), 2);
+        assert_eq!(multimap.count(&key1), 1);
+        assert!(multimap.contains_point(&15));
+
+        assert_eq!(multimap.remove(&key1), Some(()));
+        assert_eq!(multimap.remove(&key1), None);
+        assert_eq!(multimap.len(), 1);
+        assert!(!multimap.contains_point(&15));
+
+        multimap.insert(key2, ());
+        assert_eq!(multimap.remove_all(&key2), Some(vec![(), ()]));
+        assert_eq!(multimap.remove_all(&key2), None);
+        assert!(multimap.is_empty());
+        assert_eq!(multimap.iter().next(), None);
+    }
+}
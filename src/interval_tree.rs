@@ -1,13 +1,24 @@
-use crate::node::{Node, Range};
+use crate::augmented_map::Augment;
+use crate::measure::Measure;
+use crate::node::{Node, NodeIter, Range, SplitNode};
+use crate::set_ops::{Cut, Runs, SetOp};
 
 use std::borrow::Borrow;
 use std::cmp::Ordering;
 use std::cmp::Ordering::*;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
 use std::fmt;
+use std::iter;
+use std::iter::Peekable;
 use std::mem;
 use std::ops::Bound;
 use std::ops::Bound::*;
 use std::ops::RangeBounds;
+use std::ops::{BitAnd, BitOr, BitXor, Sub};
+use std::vec;
+#[cfg(any(feature="serde", test))]
+use crate::node::SerializedTree;
 #[cfg(any(feature="serde", test))]
 use serde::{Serialize, Deserialize};
 
@@ -35,12 +46,83 @@ use serde::{Serialize, Deserialize};
 /// assert_eq!(interval_tree.len(), 2);
 /// ```
 #[cfg_attr(any(feature="serde", test), derive(Serialize, Deserialize))]
-#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    any(feature="serde", test),
+    serde(
+        try_from = "SerializedTree<K, (), Size>",
+        bound(deserialize = "K: Deserialize<'de> + Ord + Clone")
+    )
+)]
+#[derive(Clone, Debug)]
 pub struct IntervalTree<K> {
-    root: Option<Box<Node<K>>>,
+    root: Option<Box<Node<K, (), Size>>>,
+    // Index of the end-points of the intervals, holding the same intervals sorted by end-point,
+    // then by start-point. It is derived from the intervals, so it is not serialized.
+    #[cfg_attr(any(feature="serde", test), serde(skip_serializing))]
+    ends: Option<Box<Node<K, (), Size>>>,
     size: usize,
 }
 
+// The index of end-points is derived from the intervals, and its shape depends on the order
+// of insertion of the end-points, so it is not compared.
+impl<K> PartialEq for IntervalTree<K>
+where
+    K: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.root == other.root && self.size == other.size
+    }
+}
+
+// The index of end-points is rebuilt from the deserialized intervals.
+#[cfg(any(feature="serde", test))]
+impl<K> TryFrom<SerializedTree<K, (), Size>> for IntervalTree<K>
+where
+    K: Ord + Clone,
+{
+    type Error = serde::de::value::Error;
+
+    fn try_from(tree: SerializedTree<K, (), Size>) -> Result<Self, Self::Error> {
+        let size = tree.size()?;
+
+        let mut ends = None;
+        for node in NodeIter::new(&tree.root) {
+            IntervalTree::insert_end(&mut ends, node.key.clone());
+        }
+
+        Ok(IntervalTree {
+            root: tree.root,
+            ends,
+            size,
+        })
+    }
+}
+
+/// Number of intervals in a subtree, maintained to answer order-statistic queries.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Size(usize);
+
+impl Size {
+    fn of<K, V>(node: &Option<Box<Node<K, V, Size>>>) -> usize {
+        node.as_ref().map_or(0, |node| node.aggregate.0)
+    }
+}
+
+// Every interval of the tree counts once.
+impl<K> Augment<K, ()> for Size {
+    fn identity() -> Self {
+        Size(0)
+    }
+
+    fn lift(_: &Range<K>, _: &()) -> Self {
+        Size(1)
+    }
+
+    fn combine(&self, other: &Self) -> Self {
+        Size(self.0 + other.0)
+    }
+}
+
 impl<K> fmt::Display for IntervalTree<K>
 where
     K: fmt::Display,
@@ -57,11 +139,19 @@ impl<K> Default for IntervalTree<K> {
     fn default() -> IntervalTree<K> {
         IntervalTree {
             root: None,
+            ends: None,
             size: 0,
         }
     }
 }
 
+impl<K> Drop for IntervalTree<K> {
+    fn drop(&mut self) {
+        Node::drop_tree(self.root.take());
+        Node::drop_tree(self.ends.take());
+    }
+}
+
 /// Creates an [`IntervalTree`] from an iterator of elements
 /// satisfying the [`RangeBounds`] trait.
 impl<K, R> FromIterator<R> for IntervalTree<K>
@@ -96,6 +186,265 @@ where
     }
 }
 
+/// Returns the union of two trees as a new tree of normalized intervals.
+/// See [`IntervalTree::union`] for more details.
+///
+/// # Examples
+///
+/// ```
+/// use unbounded_interval_tree::interval_tree::IntervalTree;
+///
+/// let a = IntervalTree::from([0..5, 10..20]);
+/// let b = IntervalTree::from([5..10]);
+///
+/// assert_eq!(&a | &b, IntervalTree::from([0..20]));
+/// ```
+impl<K> BitOr<&IntervalTree<K>> for &IntervalTree<K>
+where
+    K: Ord + Clone,
+{
+    type Output = IntervalTree<K>;
+
+    fn bitor(self, other: &IntervalTree<K>) -> IntervalTree<K> {
+        self.union(other)
+            .map(|(start, end)| (start.cloned(), end.cloned()))
+            .collect()
+    }
+}
+
+/// Returns the intersection of two trees as a new tree of normalized intervals.
+/// See [`IntervalTree::intersection`] for more details.
+///
+/// # Examples
+///
+/// ```
+/// use unbounded_interval_tree::interval_tree::IntervalTree;
+///
+/// let a = IntervalTree::from([0..5, 10..20]);
+/// let b = IntervalTree::from([3..15]);
+///
+/// assert_eq!(&a & &b, IntervalTree::from([3..5, 10..15]));
+/// ```
+impl<K> BitAnd<&IntervalTree<K>> for &IntervalTree<K>
+where
+    K: Ord + Clone,
+{
+    type Output = IntervalTree<K>;
+
+    fn bitand(self, other: &IntervalTree<K>) -> IntervalTree<K> {
+        self.intersection(other)
+            .map(|(start, end)| (start.cloned(), end.cloned()))
+            .collect()
+    }
+}
+
+/// Returns the difference between two trees as a new tree of normalized intervals.
+/// See [`IntervalTree::difference`] for more details.
+///
+/// # Examples
+///
+/// ```
+/// use unbounded_interval_tree::interval_tree::IntervalTree;
+///
+/// let a = IntervalTree::from([0..20]);
+/// let b = IntervalTree::from([5..10]);
+///
+/// assert_eq!(&a - &b, IntervalTree::from([0..5, 10..20]));
+/// ```
+impl<K> Sub<&IntervalTree<K>> for &IntervalTree<K>
+where
+    K: Ord + Clone,
+{
+    type Output = IntervalTree<K>;
+
+    fn sub(self, other: &IntervalTree<K>) -> IntervalTree<K> {
+        self.difference(other)
+            .map(|(start, end)| (start.cloned(), end.cloned()))
+            .collect()
+    }
+}
+
+/// Returns the symmetric difference between two trees as a new tree of normalized intervals.
+/// See [`IntervalTree::symmetric_difference`] for more details.
+///
+/// # Examples
+///
+/// ```
+/// use unbounded_interval_tree::interval_tree::IntervalTree;
+///
+/// let a = IntervalTree::from([0..10]);
+/// let b = IntervalTree::from([5..15]);
+///
+/// assert_eq!(&a ^ &b, IntervalTree::from([0..5, 10..15]));
+/// ```
+impl<K> BitXor<&IntervalTree<K>> for &IntervalTree<K>
+where
+    K: Ord + Clone,
+{
+    type Output = IntervalTree<K>;
+
+    fn bitxor(self, other: &IntervalTree<K>) -> IntervalTree<K> {
+        self.symmetric_difference(other)
+            .map(|(start, end)| (start.cloned(), end.cloned()))
+            .collect()
+    }
+}
+
+/// The error returned when trying to insert an invalid interval.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IntervalError {
+    /// The start bound of the interval is greater than its end bound, as in `9..5`.
+    Inverted,
+    /// The bounds of the interval are equal, but the interval contains no point
+    /// because one of them is excluded, as in `5..5`.
+    Empty,
+}
+
+impl fmt::Display for IntervalError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IntervalError::Inverted => {
+                write!(f, "the start bound of the interval is greater than its end bound")
+            }
+            IntervalError::Empty => write!(f, "the interval is empty"),
+        }
+    }
+}
+
+impl std::error::Error for IntervalError {}
+
+/// One of the 13 relations of Allen's interval algebra, exactly one of which holds
+/// between any two intervals. See [`AllenRelation::classify`].
+///
+/// Intervals are compared as sets of points, with the exact semantics of their bounds: two intervals
+/// meet when they are contiguous but share no point, as `[1, 5)` and `[5, 9]`, or `[1, 5]` and `(5, 9]`,
+/// and an unbounded start (resp. end) is only equal to another unbounded start (resp. end).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AllenRelation {
+    /// The first interval ends before the second one starts, with points between them.
+    Before,
+    /// The first interval ends right where the second one starts, with no point between them.
+    Meets,
+    /// The first interval starts first, and ends within the second one.
+    Overlaps,
+    /// Both intervals start together, and the first one ends first.
+    Starts,
+    /// The first interval starts after and ends before the second one.
+    During,
+    /// Both intervals end together, and the first one starts last.
+    Finishes,
+    /// Both intervals are the same set of points.
+    Equals,
+    /// Inverse of [`AllenRelation::Finishes`].
+    FinishedBy,
+    /// Inverse of [`AllenRelation::During`].
+    Contains,
+    /// Inverse of [`AllenRelation::Starts`].
+    StartedBy,
+    /// Inverse of [`AllenRelation::Overlaps`].
+    OverlappedBy,
+    /// Inverse of [`AllenRelation::Meets`].
+    MetBy,
+    /// Inverse of [`AllenRelation::Before`].
+    After,
+}
+
+impl AllenRelation {
+    /// Returns the relation between the intervals `a` and `b`, as in "`a` is before `b`".
+    /// Both intervals must be valid, as checked by [`IntervalTree::validate`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::ops::Bound::{Included, Excluded};
+    /// use unbounded_interval_tree::interval_tree::AllenRelation;
+    ///
+    /// assert_eq!(AllenRelation::classify(&(0..5), &(5..10)), AllenRelation::Meets);
+    /// assert_eq!(AllenRelation::classify(&(0..5), &(6..10)), AllenRelation::Before);
+    /// assert_eq!(AllenRelation::classify(&(0..=5), &(5..10)), AllenRelation::Overlaps);
+    /// assert_eq!(AllenRelation::classify(&(Included(0), Included(5)), &(Excluded(5), Excluded(10))),
+    ///            AllenRelation::Meets);
+    /// assert_eq!(AllenRelation::classify(&(..5), &(0..5)), AllenRelation::FinishedBy);
+    /// ```
+    pub fn classify<T, A, B>(a: &A, b: &B) -> AllenRelation
+    where
+        T: Ord + ?Sized,
+        A: RangeBounds<T>,
+        B: RangeBounds<T>,
+    {
+        AllenRelation::of_cuts(
+            (Cut::from_start(a.start_bound()), Cut::from_end(a.end_bound())),
+            (Cut::from_start(b.start_bound()), Cut::from_end(b.end_bound())),
+        )
+    }
+
+    fn of_cuts<T: Ord>(a: (Cut<T>, Cut<T>), b: (Cut<T>, Cut<T>)) -> AllenRelation {
+        let ((a_start, a_end), (b_start, b_end)) = (a, b);
+
+        // Disjoint intervals are either contiguous, or separated by some points.
+        match a_end.cmp(&b_start) {
+            Less => return AllenRelation::Before,
+            Equal => return AllenRelation::Meets,
+            Greater => {}
+        }
+        match b_end.cmp(&a_start) {
+            Less => return AllenRelation::After,
+            Equal => return AllenRelation::MetBy,
+            Greater => {}
+        }
+
+        match (a_start.cmp(&b_start), a_end.cmp(&b_end)) {
+            (Less, Less) => AllenRelation::Overlaps,
+            (Less, Equal) => AllenRelation::FinishedBy,
+            (Less, Greater) => AllenRelation::Contains,
+            (Equal, Less) => AllenRelation::Starts,
+            (Equal, Equal) => AllenRelation::Equals,
+            (Equal, Greater) => AllenRelation::StartedBy,
+            (Greater, Less) => AllenRelation::During,
+            (Greater, Equal) => AllenRelation::Finishes,
+            (Greater, Greater) => AllenRelation::OverlappedBy,
+        }
+    }
+
+    /// Returns the inverse relation, which holds between `b` and `a` when `self` holds between `a` and `b`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unbounded_interval_tree::interval_tree::AllenRelation;
+    ///
+    /// assert_eq!(AllenRelation::During.inverse(), AllenRelation::Contains);
+    /// assert_eq!(AllenRelation::Equals.inverse(), AllenRelation::Equals);
+    /// ```
+    pub fn inverse(self) -> AllenRelation {
+        match self {
+            AllenRelation::Before => AllenRelation::After,
+            AllenRelation::Meets => AllenRelation::MetBy,
+            AllenRelation::Overlaps => AllenRelation::OverlappedBy,
+            AllenRelation::Starts => AllenRelation::StartedBy,
+            AllenRelation::During => AllenRelation::Contains,
+            AllenRelation::Finishes => AllenRelation::FinishedBy,
+            AllenRelation::Equals => AllenRelation::Equals,
+            AllenRelation::FinishedBy => AllenRelation::Finishes,
+            AllenRelation::Contains => AllenRelation::During,
+            AllenRelation::StartedBy => AllenRelation::Starts,
+            AllenRelation::OverlappedBy => AllenRelation::Overlaps,
+            AllenRelation::MetBy => AllenRelation::Meets,
+            AllenRelation::After => AllenRelation::Before,
+        }
+    }
+}
+
+/// A boundary event of a sweep over the intervals of an [`IntervalTree`],
+/// as yielded by [`IntervalTree::events`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Event<'a, K> {
+    /// The sweep enters the interval at its start bound.
+    Enter(&'a Range<K>),
+    /// The sweep exits the interval at its end bound.
+    Exit(&'a Range<K>),
+}
+
 impl<K> IntervalTree<K> {
     /// Produces an inorder iterator for the interval tree.
     ///
@@ -119,8 +468,7 @@ impl<K> IntervalTree<K> {
     /// ```
     pub fn iter<'a>(&'a self) -> IntervalTreeIter<'a, K> {
         IntervalTreeIter {
-            to_visit: vec![],
-            curr: &self.root,
+            inner: NodeIter::new(&self.root),
         }
     }
 
@@ -128,7 +476,12 @@ impl<K> IntervalTree<K> {
     /// binary search properties of this tree.
     /// It is ok to insert a `range` that overlaps with an existing interval in the tree.
     ///
-    /// An improvement to come is to rebalance the tree (following an AVL or a red-black scheme).
+    /// The tree is rebalanced following an AVL scheme, so that its height stays logarithmic
+    /// in the number of intervals stored, regardless of the order of insertions.
+    ///
+    /// Returns whether the interval was newly inserted. That is, an interval identical
+    /// to one already stored in the tree is not inserted again. See
+    /// [`IntervalMultiMap`](crate::interval_multimap::IntervalMultiMap) to store identical intervals.
     ///
     /// # Examples
     ///
@@ -138,55 +491,135 @@ impl<K> IntervalTree<K> {
     ///
     /// let mut int_tree = IntervalTree::default();
     ///
-    /// int_tree.insert((Included(5), Excluded(9)));
-    /// int_tree.insert(..=10);
+    /// assert!(int_tree.insert((Included(5), Excluded(9))));
+    /// assert!(int_tree.insert(..=10));
+    /// assert!(!int_tree.insert(5..9));
+    /// assert_eq!(int_tree.len(), 2);
     ///
     /// let mut str_tree: IntervalTree<&str> = IntervalTree::default();
     ///
     /// str_tree.insert("Noria"..);
     /// ```
-    pub fn insert<R>(&mut self, range: R)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is inverted or empty, as these intervals would corrupt the results
+    /// of the queries. See [`IntervalTree::try_insert`] to handle invalid intervals instead.
+    /// Creating a tree with [`FromIterator`] or [`From`] panics as well.
+    ///
+    /// ```should_panic
+    /// use unbounded_interval_tree::interval_tree::IntervalTree;
+    ///
+    /// let mut tree = IntervalTree::default();
+    ///
+    /// tree.insert(9..5);
+    /// ```
+    pub fn insert<R>(&mut self, range: R) -> bool
+    where
+        K: Ord + Clone,
+        R: RangeBounds<K>,
+    {
+        match self.try_insert(range) {
+            Ok(inserted) => inserted,
+            Err(err) => panic!("cannot insert an invalid interval: {}", err),
+        }
+    }
+
+    /// Inserts an interval `range` into the interval tree, like [`IntervalTree::insert`],
+    /// unless `range` is invalid.
+    ///
+    /// Returns whether the interval was newly inserted, or an error if the start bound
+    /// of `range` is greater than its end bound ([`IntervalError::Inverted`]),
+    /// or if `range` contains no point ([`IntervalError::Empty`]).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::ops::Bound::{Included, Excluded};
+    /// use unbounded_interval_tree::interval_tree::{IntervalError, IntervalTree};
+    ///
+    /// let mut tree = IntervalTree::default();
+    ///
+    /// assert_eq!(tree.try_insert(5..9), Ok(true));
+    /// assert_eq!(tree.try_insert(5..9), Ok(false));
+    /// assert_eq!(tree.try_insert(5..=5), Ok(true));
+    /// assert_eq!(tree.try_insert(9..5), Err(IntervalError::Inverted));
+    /// assert_eq!(tree.try_insert((Excluded(3), Excluded(3))), Err(IntervalError::Empty));
+    /// assert_eq!(tree.len(), 2);
+    /// ```
+    pub fn try_insert<R>(&mut self, range: R) -> Result<bool, IntervalError>
     where
         K: Ord + Clone,
         R: RangeBounds<K>,
     {
         let range = (range.start_bound().cloned(), range.end_bound().cloned());
-        self.size += 1;
+        Self::validate(&range)?;
 
-        // If the tree is empty, put new node at the root.
-        if self.root.is_none() {
-            self.root = Some(Box::new(Node::new(range)));
-            return;
+        let key = range.clone();
+        let inserted = Self::insert_rec(&mut self.root, range, ()).is_none();
+        if inserted {
+            Self::insert_end(&mut self.ends, key);
+            self.size += 1;
         }
 
-        // Otherwise, walk down the tree and insert when we reach leaves.
-        // TODO(jonathangb): Rotate tree?
-        let mut curr = self.root.as_mut().unwrap();
-        loop {
-            curr.maybe_update_value(&range.1);
-
-            match Self::cmp(&curr.key, &range) {
-                Equal => return, // Don't insert a redundant key.
-                Less => {
-                    match curr.right {
-                        None => {
-                            curr.right = Some(Box::new(Node::new(range)));
-                            return;
-                        }
-                        Some(ref mut node) => curr = node,
-                    };
-                }
-                Greater => {
-                    match curr.left {
-                        None => {
-                            curr.left = Some(Box::new(Node::new(range)));
-                            return;
-                        }
-                        Some(ref mut node) => curr = node,
-                    };
-                }
-            };
+        Ok(inserted)
+    }
+
+    /// Inserts `range` with its associated `data` in the given subtree, and rebalances it.
+    /// If `range` is already stored, its data is replaced and the previous data is returned.
+    pub(crate) fn insert_rec<V, A>(
+        curr: &mut Option<Box<Node<K, V, A>>>,
+        range: Range<K>,
+        data: V,
+    ) -> Option<V>
+    where
+        K: Ord + Clone,
+        A: Augment<K, V>,
+    {
+        Self::insert_rec_by(curr, range, data, Self::cmp)
+    }
+
+    // Same as `insert_rec`, in a subtree sorted by `cmp` rather than by `IntervalTree::cmp`.
+    fn insert_rec_by<V, A>(
+        curr: &mut Option<Box<Node<K, V, A>>>,
+        range: Range<K>,
+        data: V,
+        cmp: fn(&Range<K>, &Range<K>) -> Ordering,
+    ) -> Option<V>
+    where
+        K: Ord + Clone,
+        A: Augment<K, V>,
+    {
+        // If we reach an empty subtree, put the new node there.
+        let node = match curr {
+            None => {
+                *curr = Some(Box::new(Node::with_data(range, data)));
+                return None;
+            }
+            Some(node) => node,
+        };
+
+        let replaced = match cmp(&node.key, &range) {
+            // Don't insert a redundant key, only replace its data.
+            Equal => {
+                let replaced = mem::replace(&mut node.data, data);
+                node.update_aggregate();
+                return Some(replaced);
+            }
+            Less => Self::insert_rec_by(&mut node.right, range, data, cmp),
+            Greater => Self::insert_rec_by(&mut node.left, range, data, cmp),
+        };
+
+        // Walking back up the tree, update the max end-points and rotate where needed.
+        // If only some data was replaced, the shape of the tree is unchanged,
+        // but the aggregates depending on that data still have to be updated.
+        if replaced.is_none() {
+            Node::rebalance(node);
+        } else {
+            node.update_aggregate();
         }
+
+        replaced
     }
 
     /// A "stabbing query" in the jargon: returns whether or not a point `p`
@@ -229,13 +662,14 @@ impl<K> IntervalTree<K> {
         K: Ord + Borrow<Q>,
         Q: Ord + ?Sized,
     {
-        self.contains_interval(&(Included(p), Included(p)))
+        self.get_point_overlaps(p).next().is_some()
     }
 
-    /// An alternative "stabbing query": returns whether or not an interval `range`
-    /// is fully covered by the intervals stored in the tree.
+    /// Returns an inorder iterator over all the intervals stored in the tree that contain
+    /// the point `p`. This answers the "stabbing query" with the actual intervals stabbed,
+    /// rather than a boolean like [`IntervalTree::contains_point`].
     ///
-    /// The given `range` may have bounds that are of a borrowed form of the stored type `K`.
+    /// The given point may be of a borrowed form of the stored type `K`.
     ///
     /// # Examples
     ///
@@ -245,271 +679,1352 @@ impl<K> IntervalTree<K> {
     ///
     /// let mut tree = IntervalTree::default();
     ///
-    /// tree.insert((Included(20), Included(30)));
-    /// tree.insert((Excluded(30), Excluded(50)));
+    /// tree.insert((Included(0), Excluded(10)));
+    /// tree.insert((Included(5), Included(20)));
+    /// tree.insert((Excluded(10), Unbounded));
     ///
-    /// assert!(tree.contains_interval(&(20..=40)));
-    /// // Borrowed form of the key works as well.
-    /// assert!(!tree.contains_interval(&(&30..=&50)));
+    /// assert_eq!(tree.get_point_overlaps(&10).collect::<Vec<_>>(),
+    ///            vec![&(Included(5), Included(20))]);
+    /// assert_eq!(tree.get_point_overlaps(&5).count(), 2);
+    /// assert_eq!(tree.get_point_overlaps(&-1).next(), None);
     /// ```
+    pub fn get_point_overlaps<'a, Q>(&'a self, p: &'a Q) -> impl Iterator<Item = &'a Range<K>>
+    where
+        K: Ord + Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        OverlapIter::new(&self.root, Included(p), Included(p)).map(|node| &node.key)
+    }
+
+    /// Returns the innermost interval stored in the tree containing the point `p`, such as
+    /// the smallest syntax span containing an offset.
     ///
-    /// Again, the given `range` can be any type implementing [`Ord`].
+    /// Intervals are ordered by their end bound, then by their start bound in reverse,
+    /// so that an interval nested in another one is always innermost. When the intervals
+    /// containing `p` are not all nested, the innermost one is therefore the interval
+    /// ending first, and ties are broken by [`IntervalTree::cmp`] in reverse: among the
+    /// intervals containing `p` that do not contain any other of them, this is the first one
+    /// in the order of the tree.
     ///
-    /// ```
-    /// use std::ops::Bound::{Included, Excluded, Unbounded};
-    /// use unbounded_interval_tree::interval_tree::IntervalTree;
+    /// The given point may be of a borrowed form of the stored type `K`.
     ///
-    /// let mut tree: IntervalTree<&str> = IntervalTree::default();
+    /// # Examples
     ///
-    /// let key1 = (Included("a"), Excluded("h"));
-    /// let key2 = (Excluded("M"), Excluded("O"));
+    /// ```
+    /// use std::ops::Bound::{Included, Excluded};
+    /// use unbounded_interval_tree::interval_tree::IntervalTree;
     ///
-    /// tree.insert(key1.clone());
-    /// tree.insert(key2);
+    /// let tree = IntervalTree::from([0..100, 10..50, 20..30, 25..40]);
     ///
-    /// assert!(tree.contains_interval(&("a".."h")));
-    /// assert!(!tree.contains_interval(&("N"..="O")));
-    /// // Sometimes, we have to disambiguate the key type.
-    /// assert!(tree.contains_interval::<&str, _>(&key1));
+    /// assert_eq!(tree.innermost_containing(&22), Some(&(Included(20), Excluded(30))));
+    /// assert_eq!(tree.innermost_containing(&27), Some(&(Included(20), Excluded(30))));
+    /// assert_eq!(tree.innermost_containing(&45), Some(&(Included(10), Excluded(50))));
+    /// assert_eq!(tree.innermost_containing(&100), None);
     /// ```
-    pub fn contains_interval<Q, R>(&self, range: &R) -> bool
+    pub fn innermost_containing<Q>(&self, p: &Q) -> Option<&Range<K>>
     where
         K: Ord + Borrow<Q>,
-        R: RangeBounds<Q>,
         Q: Ord + ?Sized,
     {
-        self.get_interval_difference(range).is_empty()
+        let overlaps = OverlapIter::new(&self.root, Included(p), Included(p)).map(|node| &node.key);
+        overlaps.min_by(|r1, r2| {
+            Cut::from_end(r1.1.as_ref())
+                .cmp(&Cut::from_end(r2.1.as_ref()))
+                .then_with(|| Self::cmp(r2, r1))
+        })
     }
 
-    /// Returns the inorder list of all references to intervals stored in the tree that overlaps
-    /// with the given `range` (partially or completely).
+    /// Returns the outermost interval stored in the tree containing the point `p`, such as
+    /// the largest syntax span containing an offset.
     ///
-    /// The given `range` may have bounds that are of a borrowed form of the stored type `K`.
+    /// Intervals are ordered by their start bound, then by their end bound in reverse,
+    /// so that an interval containing another one is always outermost. When the intervals
+    /// containing `p` are not all nested, the outermost one is therefore the interval
+    /// starting first, and ties are broken by [`IntervalTree::cmp`] in reverse: among the
+    /// intervals containing `p` that are not contained in any other of them, this is the first one
+    /// in the order of the tree.
+    ///
+    /// The given point may be of a borrowed form of the stored type `K`.
     ///
     /// # Examples
     ///
     /// ```
-    /// use std::ops::Bound::{Included, Excluded, Unbounded};
+    /// use std::ops::Bound::{Included, Excluded};
     /// use unbounded_interval_tree::interval_tree::IntervalTree;
     ///
-    /// let mut tree = IntervalTree::default();
-    ///
-    /// tree.insert((Included(0), Included(5)));
-    /// tree.insert((Included(7), Excluded(10)));
+    /// let tree = IntervalTree::from([0..100, 10..50, 10..30, 25..40, 25..150]);
     ///
-    /// assert_eq!(tree.get_interval_overlaps(&(-5..7)),
-    ///            vec![&(Included(0), Included(5))]);
-    /// // Borrowed form of the key works as well.
-    /// assert!(tree.get_interval_overlaps(&(&10..)).is_empty());
+    /// assert_eq!(tree.outermost_containing(&22), Some(&(Included(0), Excluded(100))));
+    /// assert_eq!(tree.outermost_containing(&120), Some(&(Included(25), Excluded(150))));
+    /// assert_eq!(tree.outermost_containing(&150), None);
     /// ```
-    pub fn get_interval_overlaps<Q, R>(&self, range: &R) -> Vec<&Range<K>>
+    pub fn outermost_containing<Q>(&self, p: &Q) -> Option<&Range<K>>
     where
         K: Ord + Borrow<Q>,
-        R: RangeBounds<Q>,
         Q: Ord + ?Sized,
     {
-        let curr = &self.root;
-        let mut acc = Vec::new();
-
-        Self::get_interval_overlaps_rec(curr, range, &mut acc);
-        acc
+        // Overlaps are found by start bound, so only the first ones, starting together,
+        // need to be visited.
+        let mut overlaps = OverlapIter::new(&self.root, Included(p), Included(p)).map(|node| &node.key);
+        let first = overlaps.next()?;
+        let start = Cut::from_start(first.0.as_ref());
+
+        Some(
+            overlaps
+                .take_while(|overlap| Cut::from_start(overlap.0.as_ref()) == start)
+                .last()
+                .unwrap_or(first),
+        )
     }
 
-    /// Returns the ordered list of subintervals in `range` that are not covered by the tree.
-    /// This is useful to compute what subsegments of `range` that are not covered by the intervals
-    /// stored in the tree.
+    /// Returns the interval stored in the tree ending most recently before the point `p`,
+    /// that is the interval with the greatest end bound among the ones entirely before `p`.
+    /// If several intervals end there, the first one in the order of the tree is returned.
     ///
-    /// If `range` is not covered at all, this simply returns a one element vector
-    /// containing the bounds of `range`.
+    /// The intervals are found in an index sorted by end bound, then by start bound,
+    /// so this runs in `O(log(n))`.
     ///
-    /// The given `range` may have bounds that are of a borrowed form of the stored type `K`.
-    /// Because all the bounds returned are either from the interval tree of from the `range`, we return
-    /// references to these bounds rather than clone them.
+    /// The given point may be of a borrowed form of the stored type `K`.
     ///
     /// # Examples
     ///
     /// ```
-    /// use std::ops::Bound::{Included, Excluded, Unbounded};
+    /// use std::ops::Bound::{Included, Excluded};
     /// use unbounded_interval_tree::interval_tree::IntervalTree;
     ///
-    /// let mut tree = IntervalTree::default();
-    ///
-    /// tree.insert((Included(0), Excluded(10)));
-    /// tree.insert((Excluded(10), Included(30)));
-    /// tree.insert((Excluded(50), Unbounded));
+    /// let tree = IntervalTree::from([0..10, 2..5, 20..30]);
     ///
-    /// assert_eq!(tree.get_interval_difference(&(-5..=30)),
-    ///            vec![(Included(&-5), Excluded(&0)),
-    ///                 (Included(&10), Included(&10))]);
-    /// assert_eq!(tree.get_interval_difference(&(..10)),
-    ///            vec![(Unbounded, Excluded(&0))]);
-    /// assert!(tree.get_interval_difference(&(100..)).is_empty());
+    /// assert_eq!(tree.predecessor(&15), Some(&(Included(0), Excluded(10))));
+    /// assert_eq!(tree.predecessor(&10), Some(&(Included(0), Excluded(10))));
+    /// assert_eq!(tree.predecessor(&7), Some(&(Included(2), Excluded(5))));
+    /// assert_eq!(tree.predecessor(&0), None);
     /// ```
-    pub fn get_interval_difference<'a, Q, R>(&'a self, range: &'a R) -> Vec<Range<&'a Q>>
+    pub fn predecessor<Q>(&self, p: &Q) -> Option<&Range<K>>
     where
         K: Ord + Borrow<Q>,
-        R: RangeBounds<Q>,
         Q: Ord + ?Sized,
     {
-        let overlaps = self.get_interval_overlaps(range);
-
-        // If there is no overlap, then the difference is the query `q` itself.
-        if overlaps.is_empty() {
-            let min = match range.start_bound() {
-                Included(x) => Included(x),
-                Excluded(x) => Excluded(x),
-                Unbounded => Unbounded,
-            };
-            let max = match range.end_bound() {
-                Included(x) => Included(x),
-                Excluded(x) => Excluded(x),
-                Unbounded => Unbounded,
-            };
-            return vec![(min, max)];
+        // Find the greatest end-point where node.max < p.
+        let mut end = None;
+        let mut curr = &self.ends;
+        while let Some(node) = curr {
+            let node_end = Cut::from_end(node.key.1.as_ref().map(Borrow::borrow));
+            if node_end <= Cut::Below(p) {
+                end = Some(node_end);
+                curr = &node.right;
+            } else {
+                curr = &node.left;
+            }
+        }
+        let end = end?;
+
+        // Then find the first interval ending there, as the index is sorted by start-point
+        // among the intervals ending together.
+        let mut predecessor = None;
+        let mut curr = &self.ends;
+        while let Some(node) = curr {
+            match Cut::from_end(node.key.1.as_ref().map(Borrow::borrow)).cmp(&end) {
+                Less => curr = &node.right,
+                Equal => {
+                    predecessor = Some(&node.key);
+                    curr = &node.left;
+                }
+                Greater => curr = &node.left,
+            }
+        }
+
+        predecessor
+    }
+
+    /// Returns the interval stored in the tree starting soonest after the point `p`,
+    /// that is the interval with the smallest start bound among the ones entirely after `p`.
+    /// If several intervals start there, the first one in the order of the tree is returned.
+    ///
+    /// This runs in `O(log(n))`, as the tree is ordered by start bound.
+    ///
+    /// The given point may be of a borrowed form of the stored type `K`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::ops::Bound::{Included, Excluded};
+    /// use unbounded_interval_tree::interval_tree::IntervalTree;
+    ///
+    /// let tree = IntervalTree::from([0..10, 20..30, 20..25]);
+    ///
+    /// assert_eq!(tree.successor(&15), Some(&(Included(20), Excluded(25))));
+    /// assert_eq!(tree.successor(&5), Some(&(Included(20), Excluded(25))));
+    /// assert_eq!(tree.successor(&-1), Some(&(Included(0), Excluded(10))));
+    /// assert_eq!(tree.successor(&20), None);
+    /// ```
+    pub fn successor<Q>(&self, p: &Q) -> Option<&Range<K>>
+    where
+        K: Ord + Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        // Find the first node where node.min > p.
+        let mut successor = None;
+        let mut curr = &self.root;
+        while let Some(node) = curr {
+            if Cut::from_start(node.key.0.as_ref().map(Borrow::borrow)) >= Cut::Above(p) {
+                successor = Some(&node.key);
+                curr = &node.left;
+            } else {
+                curr = &node.right;
+            }
+        }
+
+        successor
+    }
+
+    /// Returns the interval stored in the tree nearest to the point `p`. If some intervals
+    /// contain `p`, this is the first of them in the order of the tree. Otherwise, this is either
+    /// the [predecessor](IntervalTree::predecessor) or the [successor](IntervalTree::successor)
+    /// of `p`, whichever is the closest to `p` according to the [`Measure`] of the keys.
+    /// In case of a tie, the predecessor is returned.
+    ///
+    /// The given point may be of a borrowed form of the stored type `K`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::ops::Bound::{Included, Excluded};
+    /// use unbounded_interval_tree::interval_tree::IntervalTree;
+    ///
+    /// let tree = IntervalTree::from([0..10, 20..30]);
+    ///
+    /// assert_eq!(tree.nearest(&5), Some(&(Included(0), Excluded(10))));
+    /// assert_eq!(tree.nearest(&14), Some(&(Included(0), Excluded(10))));
+    /// assert_eq!(tree.nearest(&16), Some(&(Included(20), Excluded(30))));
+    /// assert_eq!(tree.nearest(&100), Some(&(Included(20), Excluded(30))));
+    /// ```
+    pub fn nearest<Q>(&self, p: &Q) -> Option<&Range<K>>
+    where
+        K: Ord + Borrow<Q>,
+        Q: Measure + Ord + ?Sized,
+    {
+        if let Some(containing) = OverlapIter::new(&self.root, Included(p), Included(p)).next() {
+            return Some(&containing.key);
+        }
+
+        match (self.predecessor(p), self.successor(p)) {
+            (Some(predecessor), Some(successor)) => {
+                // The predecessor has a bounded end, and the successor has a bounded start.
+                let before = match &predecessor.1 {
+                    Included(end) | Excluded(end) => Q::length(end.borrow(), p),
+                    Unbounded => unreachable!("the predecessor of a point ends before it"),
+                };
+                let after = match &successor.0 {
+                    Included(start) | Excluded(start) => Q::length(p, start.borrow()),
+                    Unbounded => unreachable!("the successor of a point starts after it"),
+                };
+
+                if before <= after {
+                    Some(predecessor)
+                } else {
+                    Some(successor)
+                }
+            }
+            (predecessor, successor) => predecessor.or(successor),
+        }
+    }
+
+    /// An alternative "stabbing query": returns whether or not an interval `range`
+    /// is fully covered by the intervals stored in the tree.
+    ///
+    /// The given `range` may have bounds that are of a borrowed form of the stored type `K`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::ops::Bound::{Included, Excluded, Unbounded};
+    /// use unbounded_interval_tree::interval_tree::IntervalTree;
+    ///
+    /// let mut tree = IntervalTree::default();
+    ///
+    /// tree.insert((Included(20), Included(30)));
+    /// tree.insert((Excluded(30), Excluded(50)));
+    ///
+    /// assert!(tree.contains_interval(&(20..=40)));
+    /// // Borrowed form of the key works as well.
+    /// assert!(!tree.contains_interval(&(&30..=&50)));
+    /// ```
+    ///
+    /// Again, the given `range` can be any type implementing [`Ord`].
+    ///
+    /// ```
+    /// use std::ops::Bound::{Included, Excluded, Unbounded};
+    /// use unbounded_interval_tree::interval_tree::IntervalTree;
+    ///
+    /// let mut tree: IntervalTree<&str> = IntervalTree::default();
+    ///
+    /// let key1 = (Included("a"), Excluded("h"));
+    /// let key2 = (Excluded("M"), Excluded("O"));
+    ///
+    /// tree.insert(key1.clone());
+    /// tree.insert(key2);
+    ///
+    /// assert!(tree.contains_interval(&("a".."h")));
+    /// assert!(!tree.contains_interval(&("N"..="O")));
+    /// // Sometimes, we have to disambiguate the key type.
+    /// assert!(tree.contains_interval::<&str, _>(&key1));
+    /// ```
+    pub fn contains_interval<Q, R>(&self, range: &R) -> bool
+    where
+        K: Ord + Borrow<Q>,
+        R: RangeBounds<Q>,
+        Q: Ord + ?Sized,
+    {
+        Self::covers(&self.root, range)
+    }
+
+    /// Returns an inorder iterator over all the intervals stored in the tree that overlap
+    /// with the given `range` (partially or completely).
+    ///
+    /// Intervals are found lazily while walking the tree, so no allocation is needed
+    /// beyond a stack as deep as the tree, and stopping the iteration early skips
+    /// the rest of the search.
+    ///
+    /// The given `range` may have bounds that are of a borrowed form of the stored type `K`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::ops::Bound::{Included, Excluded};
+    /// use unbounded_interval_tree::interval_tree::IntervalTree;
+    ///
+    /// let tree = (0..1000).map(|i| i..i + 10).collect::<IntervalTree<_>>();
+    ///
+    /// let mut overlaps = tree.overlaps(&(500..=505));
+    /// assert_eq!(overlaps.next(), Some(&(Included(491), Excluded(501))));
+    /// assert_eq!(overlaps.next(), Some(&(Included(492), Excluded(502))));
+    ///
+    /// assert_eq!(tree.overlaps(&(2000..)).next(), None);
+    /// ```
+    pub fn overlaps<'a, Q, R>(&'a self, range: &'a R) -> impl Iterator<Item = &'a Range<K>>
+    where
+        K: Ord + Borrow<Q>,
+        R: RangeBounds<Q>,
+        Q: Ord + ?Sized + 'a,
+    {
+        OverlapIter::new(&self.root, range.start_bound(), range.end_bound()).map(|node| &node.key)
+    }
+
+    /// Returns the inorder list of all references to intervals stored in the tree that overlaps
+    /// with the given `range` (partially or completely).
+    /// This collects the intervals yielded by [`IntervalTree::overlaps`].
+    ///
+    /// The given `range` may have bounds that are of a borrowed form of the stored type `K`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::ops::Bound::{Included, Excluded, Unbounded};
+    /// use unbounded_interval_tree::interval_tree::IntervalTree;
+    ///
+    /// let mut tree = IntervalTree::default();
+    ///
+    /// tree.insert((Included(0), Included(5)));
+    /// tree.insert((Included(7), Excluded(10)));
+    ///
+    /// assert_eq!(tree.get_interval_overlaps(&(-5..7)),
+    ///            vec![&(Included(0), Included(5))]);
+    /// // Borrowed form of the key works as well.
+    /// assert!(tree.get_interval_overlaps(&(&10..)).is_empty());
+    /// ```
+    pub fn get_interval_overlaps<Q, R>(&self, range: &R) -> Vec<&Range<K>>
+    where
+        K: Ord + Borrow<Q>,
+        R: RangeBounds<Q>,
+        Q: Ord + ?Sized,
+    {
+        OverlapIter::new(&self.root, range.start_bound(), range.end_bound())
+            .map(|node| &node.key)
+            .collect()
+    }
+
+    /// Returns an inorder iterator over the intervals stored in the tree that are contained in
+    /// the given `range`, that is whose points are all in `range`.
+    ///
+    /// Unlike filtering the intervals yielded by [`IntervalTree::overlaps`], the subtrees
+    /// of intervals starting before `range` are skipped, along with the subtrees of intervals
+    /// all ending before it. Intervals are found lazily while walking the tree.
+    /// If `range` is empty or inverted, no interval is returned.
+    ///
+    /// The given `range` may have bounds that are of a borrowed form of the stored type `K`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::ops::Bound::{Included, Excluded};
+    /// use unbounded_interval_tree::interval_tree::IntervalTree;
+    ///
+    /// let tree = IntervalTree::from([0..10, 2..5, 5..8, 8..12]);
+    ///
+    /// assert_eq!(tree.get_contained_in(&(2..10)).collect::<Vec<_>>(),
+    ///            vec![&(Included(2), Excluded(5)), &(Included(5), Excluded(8))]);
+    /// assert_eq!(tree.get_contained_in(&(3..=7)).next(), None);
+    /// ```
+    pub fn get_contained_in<'a, Q, R>(&'a self, range: &'a R) -> impl Iterator<Item = &'a Range<K>>
+    where
+        K: Ord + Borrow<Q>,
+        R: RangeBounds<Q>,
+        Q: Ord + ?Sized + 'a,
+    {
+        let start = Cut::from_start(range.start_bound());
+        let end = Cut::from_end(range.end_bound());
+
+        // An empty or inverted range contains no interval.
+        let root = if start < end { &self.root } else { &None };
+        PrunedIter::new(root, (Included(start), Excluded(end)), start)
+            .map(|node| &node.key)
+            .filter(move |key| Cut::from_end(key.1.as_ref().map(Borrow::borrow)) <= end)
+    }
+
+    /// Returns an inorder iterator over the intervals stored in the tree that contain
+    /// the given `range`, that is which contain all the points of `range`.
+    ///
+    /// Unlike filtering the intervals yielded by [`IntervalTree::overlaps`], the subtrees
+    /// of intervals all ending before the end of `range` are skipped, and the walk stops
+    /// at the first interval starting after the start of `range`. Intervals are found lazily
+    /// while walking the tree. If `range` is empty or inverted, no interval is returned.
+    ///
+    /// The given `range` may have bounds that are of a borrowed form of the stored type `K`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::ops::Bound::{Included, Excluded};
+    /// use unbounded_interval_tree::interval_tree::IntervalTree;
+    ///
+    /// let tree = IntervalTree::from([0..10, 2..5, 3..7, 5..8]);
+    ///
+    /// assert_eq!(tree.get_containing(&(3..5)).collect::<Vec<_>>(),
+    ///            vec![&(Included(0), Excluded(10)), &(Included(2), Excluded(5)), &(Included(3), Excluded(7))]);
+    /// assert_eq!(tree.get_containing(&(8..=10)).next(), None);
+    /// ```
+    pub fn get_containing<'a, Q, R>(&'a self, range: &'a R) -> impl Iterator<Item = &'a Range<K>>
+    where
+        K: Ord + Borrow<Q>,
+        R: RangeBounds<Q>,
+        Q: Ord + ?Sized + 'a,
+    {
+        let start = Cut::from_start(range.start_bound());
+        let end = Cut::from_end(range.end_bound());
+
+        // An empty or inverted range is not contained in any interval.
+        let root = if start < end { &self.root } else { &None };
+        PrunedIter::new(root, (Unbounded, Included(start)), end).map(|node| &node.key)
+    }
+
+    /// Returns an inorder iterator over the intervals stored in the tree starting in the given `range`,
+    /// that is whose first points are in `range`, such as the sessions that started during the last hour.
+    ///
+    /// Start bounds follow the same semantics as in [`IntervalTree::cmp`]: `(5, 9]` does not start
+    /// in `[0, 5]`, but starts in `(5, 7)`. Intervals with an unbounded start only start in
+    /// ranges with an unbounded start as well.
+    ///
+    /// As the tree is ordered by start bound, only the subtrees of intervals starting
+    /// in `range` are visited. Intervals are found lazily while walking the tree.
+    /// If `range` is empty or inverted, no interval is returned.
+    ///
+    /// The given `range` may have bounds that are of a borrowed form of the stored type `K`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::ops::Bound::{Included, Excluded};
+    /// use unbounded_interval_tree::interval_tree::IntervalTree;
+    ///
+    /// let tree = IntervalTree::from([0..10, 5..15, 10..20]);
+    ///
+    /// assert_eq!(tree.starting_in(&(5..=10)).collect::<Vec<_>>(),
+    ///            vec![&(Included(5), Excluded(15)), &(Included(10), Excluded(20))]);
+    /// assert_eq!(tree.starting_in(&(1..5)).next(), None);
+    /// ```
+    pub fn starting_in<'a, Q, R>(&'a self, range: &'a R) -> impl Iterator<Item = &'a Range<K>>
+    where
+        K: Ord + Borrow<Q>,
+        R: RangeBounds<Q>,
+        Q: Ord + ?Sized + 'a,
+    {
+        let start = Cut::from_start(range.start_bound());
+        let end = Cut::from_end(range.end_bound());
+
+        // No interval starts in an empty or inverted range.
+        let root = if start < end { &self.root } else { &None };
+        PrunedIter::new(root, (Included(start), Excluded(end)), Cut::BelowAll).map(|node| &node.key)
+    }
+
+    /// Returns an inorder iterator over the intervals stored in the tree ending in the given `range`,
+    /// that is whose last points are in `range`, such as the sessions that ended during the last hour.
+    ///
+    /// End bounds follow the same semantics as in [`IntervalTree::cmp`]: `[0, 5)` does not end
+    /// in `[5, 9]`, but ends in `(3, 5)`. Intervals with an unbounded end only end in
+    /// ranges with an unbounded end as well.
+    ///
+    /// The subtrees of intervals all ending before `range`, or all starting after it, are skipped,
+    /// so only the intervals ending in `range` and the ones containing its end are visited.
+    /// Intervals are found lazily while walking the tree.
+    /// If `range` is empty or inverted, no interval is returned.
+    ///
+    /// The given `range` may have bounds that are of a borrowed form of the stored type `K`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::ops::Bound::{Included, Excluded};
+    /// use unbounded_interval_tree::interval_tree::IntervalTree;
+    ///
+    /// let tree = IntervalTree::from([0..10, 5..15, 10..20]);
+    ///
+    /// assert_eq!(tree.ending_in(&(5..=15)).collect::<Vec<_>>(),
+    ///            vec![&(Included(0), Excluded(10)), &(Included(5), Excluded(15))]);
+    /// assert_eq!(tree.ending_in(&(20..25)).next(), None);
+    /// ```
+    pub fn ending_in<'a, Q, R>(&'a self, range: &'a R) -> impl Iterator<Item = &'a Range<K>>
+    where
+        K: Ord + Borrow<Q>,
+        R: RangeBounds<Q>,
+        Q: Ord + ?Sized + 'a,
+    {
+        let start = Cut::from_start(range.start_bound());
+        let end = Cut::from_end(range.end_bound());
+
+        // No interval ends in an empty or inverted range. Intervals ending in `range`
+        // start before its end, and end after its start.
+        let root = if start < end { &self.root } else { &None };
+        PrunedIter::new(root, (Unbounded, Excluded(end)), start)
+            .map(|node| &node.key)
+            .filter(move |key| {
+                let key_end = Cut::from_end(key.1.as_ref().map(Borrow::borrow));
+                start < key_end && key_end <= end
+            })
+    }
+
+    /// Returns an inorder iterator over the intervals stored in the tree that are in the given
+    /// `relation` with `range`, as in "the interval is before `range`".
+    /// See [`AllenRelation`] for the exact semantics of the relations.
+    ///
+    /// Every relation bounds the start of the intervals, and most also bound their end from below,
+    /// so the subtrees that cannot hold any such interval are skipped, using both the start ordering
+    /// and the maximum end of the intervals. Intervals are found lazily while walking the tree.
+    /// If `range` is empty or inverted, no interval is returned.
+    ///
+    /// The given `range` may have bounds that are of a borrowed form of the stored type `K`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::ops::Bound::{Included, Excluded};
+    /// use unbounded_interval_tree::interval_tree::{AllenRelation, IntervalTree};
+    ///
+    /// let tree = IntervalTree::from([0..5, 0..10, 5..10, 3..7, 10..12]);
+    ///
+    /// assert_eq!(tree.query_relation(&(5..10), AllenRelation::Meets).collect::<Vec<_>>(),
+    ///            vec![&(Included(0), Excluded(5))]);
+    /// assert_eq!(tree.query_relation(&(5..10), AllenRelation::MetBy).collect::<Vec<_>>(),
+    ///            vec![&(Included(10), Excluded(12))]);
+    /// assert_eq!(tree.query_relation(&(5..10), AllenRelation::Finishes).next(), None);
+    /// assert_eq!(tree.query_relation(&(5..10), AllenRelation::FinishedBy).collect::<Vec<_>>(),
+    ///            vec![&(Included(0), Excluded(10))]);
+    /// assert_eq!(tree.query_relation(&(0..5), AllenRelation::Starts).next(), None);
+    /// assert_eq!(tree.query_relation(&(0..5), AllenRelation::StartedBy).collect::<Vec<_>>(),
+    ///            vec![&(Included(0), Excluded(10))]);
+    /// ```
+    pub fn query_relation<'a, Q, R>(
+        &'a self,
+        range: &'a R,
+        relation: AllenRelation,
+    ) -> impl Iterator<Item = &'a Range<K>>
+    where
+        K: Ord + Borrow<Q>,
+        R: RangeBounds<Q>,
+        Q: Ord + ?Sized + 'a,
+    {
+        let start = Cut::from_start(range.start_bound());
+        let end = Cut::from_end(range.end_bound());
+
+        // Bounds on the start and on the end of the intervals in `relation` with `range`.
+        let (starts, min_end) = match relation {
+            AllenRelation::Before => ((Unbounded, Excluded(start)), Cut::BelowAll),
+            AllenRelation::Meets | AllenRelation::Overlaps => ((Unbounded, Excluded(start)), start),
+            AllenRelation::FinishedBy | AllenRelation::Contains => ((Unbounded, Excluded(start)), end),
+            AllenRelation::Starts => ((Included(start), Included(start)), start),
+            AllenRelation::Equals | AllenRelation::StartedBy => ((Included(start), Included(start)), end),
+            AllenRelation::During => ((Excluded(start), Excluded(end)), start),
+            AllenRelation::Finishes | AllenRelation::OverlappedBy => ((Excluded(start), Excluded(end)), end),
+            AllenRelation::MetBy => ((Included(end), Included(end)), Cut::BelowAll),
+            AllenRelation::After => ((Excluded(end), Unbounded), Cut::BelowAll),
+        };
+
+        // An empty or inverted range is in no relation with any interval.
+        let root = if start < end { &self.root } else { &None };
+        PrunedIter::new(root, starts, min_end).map(|node| &node.key).filter(move |key| {
+            let key_start = Cut::from_start(key.0.as_ref().map(Borrow::borrow));
+            let key_end = Cut::from_end(key.1.as_ref().map(Borrow::borrow));
+            AllenRelation::of_cuts((key_start, key_end), (start, end)) == relation
+        })
+    }
+
+    /// Returns the number of intervals stored in the tree that contain the point `p`.
+    /// See [`IntervalTree::count_overlapping`] for more details.
+    ///
+    /// The given point may be of a borrowed form of the stored type `K`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::ops::Bound::{Included, Excluded, Unbounded};
+    /// use unbounded_interval_tree::interval_tree::IntervalTree;
+    ///
+    /// let tree = IntervalTree::from([
+    ///     (Included(0), Excluded(10)),
+    ///     (Excluded(5), Included(15)),
+    ///     (Unbounded, Included(5)),
+    /// ]);
+    ///
+    /// assert_eq!(tree.count_containing(&5), 2);
+    /// assert_eq!(tree.count_containing(&10), 1);
+    /// assert_eq!(tree.count_containing(&20), 0);
+    /// ```
+    pub fn count_containing<Q>(&self, p: &Q) -> usize
+    where
+        K: Ord + Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.count_overlapping(&(Included(p), Included(p)))
+    }
+
+    /// Returns the number of intervals stored in the tree that overlap with the given `range`
+    /// (partially or completely), without enumerating them.
+    ///
+    /// An interval overlaps with `range` unless it starts after `range`, or ends before it.
+    /// The intervals not starting after `range` are counted in the tree, which is sorted by
+    /// start-point, while the ones ending before `range` are counted in an index of end-points.
+    /// Both indexes store the number of intervals of every subtree, so this runs in `O(log(n))`.
+    ///
+    /// If `range` is empty or inverted, it overlaps with no interval.
+    ///
+    /// The given `range` may have bounds that are of a borrowed form of the stored type `K`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::ops::Bound::{Included, Excluded};
+    /// use unbounded_interval_tree::interval_tree::IntervalTree;
+    ///
+    /// let tree = IntervalTree::from([0..10, 5..15, 20..30]);
+    ///
+    /// assert_eq!(tree.count_overlapping(&(8..20)), 2);
+    /// assert_eq!(tree.count_overlapping(&(8..=20)), 3);
+    /// assert_eq!(tree.count_overlapping(&(Excluded(15), Excluded(20))), 0);
+    /// assert_eq!(tree.count_overlapping(&(20..8)), 0);
+    /// ```
+    pub fn count_overlapping<Q, R>(&self, range: &R) -> usize
+    where
+        K: Ord + Borrow<Q>,
+        R: RangeBounds<Q>,
+        Q: Ord + ?Sized,
+    {
+        let is_empty = match (range.start_bound(), range.end_bound()) {
+            (Included(start), Included(end)) => start > end,
+            (Included(start) | Excluded(start), Included(end) | Excluded(end)) => start >= end,
+            _ => false,
+        };
+        if is_empty {
+            return 0;
+        }
+
+        // Count the intervals where node.min <= q.max.
+        let mut not_after = 0;
+        let mut curr = &self.root;
+        while let Some(node) = curr {
+            if Self::is_startbound_after(&node.key.0, range) {
+                curr = &node.left;
+            } else {
+                not_after += Size::of(&node.left) + 1;
+                curr = &node.right;
+            }
+        }
+
+        // Count the intervals where node.max < q.min, which all are among the ones above.
+        let mut before = 0;
+        let mut curr = &self.ends;
+        while let Some(node) = curr {
+            if Self::is_endbound_before(&node.key.1, range) {
+                before += Size::of(&node.left) + 1;
+                curr = &node.right;
+            } else {
+                curr = &node.left;
+            }
+        }
+
+        not_after - before
+    }
+
+    /// Returns the ordered segments of `range` along with their depth, that is the number
+    /// of intervals stored in the tree containing all the points of the segment.
+    /// Consecutive segments have distinct depths, and together they cover all of `range`,
+    /// including the segments not covered by the tree, whose depth is 0.
+    ///
+    /// As for [`IntervalTree::get_interval_difference`], an Included+Excluded at the same point
+    /// is contiguous, and the bounds returned are references to the bounds of the tree or of `range`.
+    /// If `range` is empty or inverted, no segment is returned.
+    ///
+    /// The given `range` may have bounds that are of a borrowed form of the stored type `K`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::ops::Bound::{Included, Excluded};
+    /// use unbounded_interval_tree::interval_tree::IntervalTree;
+    ///
+    /// let tree = IntervalTree::from([0..=5, 5..=9]);
+    ///
+    /// assert_eq!(tree.depth_profile(&(-2..8)).collect::<Vec<_>>(), vec![
+    ///     ((Included(&-2), Excluded(&0)), 0),
+    ///     ((Included(&0), Excluded(&5)), 1),
+    ///     ((Included(&5), Included(&5)), 2),
+    ///     ((Excluded(&5), Excluded(&8)), 1),
+    /// ]);
+    /// ```
+    pub fn depth_profile<'a, Q, R>(&'a self, range: &'a R) -> impl Iterator<Item = (Range<&'a Q>, usize)>
+    where
+        K: Ord + Borrow<Q>,
+        R: RangeBounds<Q>,
+        Q: Ord + ?Sized + 'a,
+    {
+        let mut sweep = Sweep::new(&self.root, range);
+        let mut segments: Vec<(Range<&Q>, usize)> = vec![];
+
+        while let Some((segment, covering)) = sweep.next_segment() {
+            let depth = covering.len();
+            match segments.last_mut() {
+                Some((last, last_depth)) if *last_depth == depth => last.1 = segment.1,
+                _ => segments.push((segment, depth)),
+            }
+        }
+
+        segments.into_iter()
+    }
+
+    /// Returns the ordered elementary segments of `range`, along with the ordered list of
+    /// intervals stored in the tree covering them. The intervals containing all the points of a
+    /// segment are the same, and change from one segment to the next.
+    /// Together, the segments tile all of `range`, including the segments not covered by the tree,
+    /// which are covered by no interval.
+    ///
+    /// As for [`IntervalTree::get_interval_difference`], an Included+Excluded at the same point
+    /// is contiguous, and the bounds returned are references to the bounds of the tree or of `range`.
+    /// If `range` is empty or inverted, no segment is returned.
+    ///
+    /// The given `range` may have bounds that are of a borrowed form of the stored type `K`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::ops::Bound::{Included, Excluded};
+    /// use unbounded_interval_tree::interval_tree::IntervalTree;
+    ///
+    /// let tree = IntervalTree::from([0..10, 5..15]);
+    ///
+    /// assert_eq!(tree.segment(&(-5..=12)), vec![
+    ///     ((Included(&-5), Excluded(&0)), vec![]),
+    ///     ((Included(&0), Excluded(&5)), vec![&(Included(0), Excluded(10))]),
+    ///     ((Included(&5), Excluded(&10)), vec![&(Included(0), Excluded(10)), &(Included(5), Excluded(15))]),
+    ///     ((Included(&10), Included(&12)), vec![&(Included(5), Excluded(15))]),
+    /// ]);
+    /// ```
+    pub fn segment<'a, Q, R>(&'a self, range: &'a R) -> Vec<(Range<&'a Q>, Vec<&'a Range<K>>)>
+    where
+        K: Ord + Borrow<Q>,
+        R: RangeBounds<Q>,
+        Q: Ord + ?Sized + 'a,
+    {
+        let mut sweep = Sweep::new(&self.root, range);
+        let mut segments = vec![];
+
+        while let Some((segment, covering)) = sweep.next_segment() {
+            segments.push((segment, covering.to_vec()));
         }
 
-        let mut acc = Vec::new();
-        let first = overlaps.first().unwrap();
+        segments
+    }
+
+    /// Returns an iterator over the boundary events of the intervals stored in the tree
+    /// that overlap with the given `range`: every interval yields an [`Event::Enter`] at its
+    /// start bound and an [`Event::Exit`] at its end bound, and events are ordered by the position
+    /// of their bound, as a sweep from left to right would meet them.
+    ///
+    /// Bounds at the same key are ordered consistently with [`IntervalTree::cmp`]: an
+    /// `Excluded(x)` end comes before an `Included(x)` start, which comes before an `Included(x)` end,
+    /// which comes before an `Excluded(x)` start. Hence, an interval is exited before another one
+    /// is entered exactly when they share no point. Events at the same position are yielded
+    /// in the order of their intervals.
+    ///
+    /// Events are produced lazily, with the exits of the intervals entered so far kept in a heap.
+    ///
+    /// The given `range` may have bounds that are of a borrowed form of the stored type `K`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::ops::Bound::{Included, Excluded};
+    /// use unbounded_interval_tree::interval_tree::{Event, IntervalTree};
+    ///
+    /// let tree = IntervalTree::from([0..5, 5..10, 2..4]);
+    ///
+    /// assert_eq!(tree.events(&(..)).collect::<Vec<_>>(), vec![
+    ///     Event::Enter(&(Included(0), Excluded(5))),
+    ///     Event::Enter(&(Included(2), Excluded(4))),
+    ///     Event::Exit(&(Included(2), Excluded(4))),
+    ///     Event::Exit(&(Included(0), Excluded(5))),
+    ///     Event::Enter(&(Included(5), Excluded(10))),
+    ///     Event::Exit(&(Included(5), Excluded(10))),
+    /// ]);
+    /// ```
+    pub fn events<'a, Q, R>(&'a self, range: &'a R) -> impl Iterator<Item = Event<'a, K>>
+    where
+        K: Ord + Borrow<Q>,
+        R: RangeBounds<Q>,
+        Q: Ord + ?Sized + 'a,
+    {
+        // Intervals are visited inorder, hence by start bound.
+        let mut enters = OverlapIter::new(&self.root, range.start_bound(), range.end_bound())
+            .map(|node| &node.key)
+            .peekable();
+        let mut exits = BinaryHeap::new();
+
+        iter::from_fn(move || {
+            let next_enter = enters.peek().map(|key| Cut::from_start(key.0.as_ref()));
+            let next_exit = exits.peek().map(|Reverse(ByEnd(key))| Cut::from_end(key.1.as_ref()));
+
+            match (next_enter, next_exit) {
+                (Some(enter), Some(exit)) if exit <= enter => exits.pop().map(|Reverse(ByEnd(key))| Event::Exit(key)),
+                (Some(_), _) => {
+                    let key = enters.next()?;
+                    exits.push(Reverse(ByEnd(key)));
+                    Some(Event::Enter(key))
+                }
+                (None, _) => exits.pop().map(|Reverse(ByEnd(key))| Event::Exit(key)),
+            }
+        })
+    }
+
+    /// Returns the maximum depth over `range`, that is the maximum number of intervals
+    /// stored in the tree overlapping at a same point of `range`, along with the ordered
+    /// segments of `range` reaching it. See [`IntervalTree::depth_profile`] for more details.
+    ///
+    /// If `range` is empty or inverted, the maximum depth is 0 and no segment is returned.
+    ///
+    /// The given `range` may have bounds that are of a borrowed form of the stored type `K`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::ops::Bound::{Included, Excluded};
+    /// use unbounded_interval_tree::interval_tree::IntervalTree;
+    ///
+    /// let tree = IntervalTree::from([0..10, 5..15, 8..20, 18..25]);
+    ///
+    /// assert_eq!(tree.max_depth(&(0..30)),
+    ///            (3, vec![(Included(&8), Excluded(&10))]));
+    /// assert_eq!(tree.max_depth(&(12..30)),
+    ///            (2, vec![(Included(&12), Excluded(&15)), (Included(&18), Excluded(&20))]));
+    /// ```
+    pub fn max_depth<'a, Q, R>(&'a self, range: &'a R) -> (usize, Vec<Range<&'a Q>>)
+    where
+        K: Ord + Borrow<Q>,
+        R: RangeBounds<Q>,
+        Q: Ord + ?Sized + 'a,
+    {
+        let mut max_depth = 0;
+        let mut segments = vec![];
+
+        for (segment, depth) in self.depth_profile(range) {
+            if depth > max_depth {
+                max_depth = depth;
+                segments.clear();
+            }
+            if depth == max_depth {
+                segments.push(segment);
+            }
+        }
+
+        (max_depth, segments)
+    }
+
+    /// Returns the total measure of the points of `range` covered by the intervals stored in the tree,
+    /// such as the number of seconds of a day covered by a tree of time intervals. Overlapping
+    /// intervals are only measured once. See the [`Measure`] trait for more details.
+    ///
+    /// Returns `None` if the measure is infinite, that is if an unbounded part of `range` is covered.
+    /// If `range` is empty or inverted, the measure is zero.
+    ///
+    /// The given `range` may have bounds that are of a borrowed form of the stored type `K`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unbounded_interval_tree::interval_tree::IntervalTree;
+    ///
+    /// let mut tree = IntervalTree::from([0u32..10, 5..15]);
+    /// tree.insert(20..);
+    ///
+    /// assert_eq!(tree.covered_measure(&(0..30)), Some(25));
+    /// assert_eq!(tree.covered_measure(&(12..=18)), Some(3));
+    /// assert_eq!(tree.covered_measure(&(10..)), None);
+    /// ```
+    pub fn covered_measure<Q, R>(&self, range: &R) -> Option<Q::Length>
+    where
+        K: Ord + Borrow<Q>,
+        R: RangeBounds<Q>,
+        Q: Measure + Ord + ?Sized,
+    {
+        let start = Cut::from_start(range.start_bound());
+        let end = Cut::from_end(range.end_bound());
+        if start >= end {
+            return Some(Q::Length::default());
+        }
+
+        let overlaps = OverlapIter::new(&self.root, range.start_bound(), range.end_bound()).map(|node| &node.key);
+        Runs::new(overlaps).try_fold(Q::Length::default(), |measure, (run_start, run_end)| {
+            let run_start = run_start.map(Borrow::borrow).max(start);
+            let run_end = run_end.map(Borrow::borrow).min(end);
+            Some(measure + Self::measure(run_start, run_end)?)
+        })
+    }
+
+    /// Returns the total measure of the points of `range` that are not covered by the tree,
+    /// that is the measure of the subintervals yielded by [`IntervalTree::gaps`], along with
+    /// the points following the last interval overlapping `range` if it is unbounded above.
+    /// See [`IntervalTree::covered_measure`] for more details.
+    ///
+    /// Returns `None` if the measure is infinite, that is if an unbounded part of `range` is not covered.
+    /// If `range` is empty or inverted, the measure is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unbounded_interval_tree::interval_tree::IntervalTree;
+    ///
+    /// let mut tree = IntervalTree::from([0u32..10, 5..15]);
+    /// tree.insert(20..);
+    ///
+    /// assert_eq!(tree.uncovered_measure(&(0..30)), Some(5));
+    /// assert_eq!(tree.uncovered_measure(&(10..)), Some(5));
+    /// assert_eq!(tree.uncovered_measure(&(..10)), None);
+    /// ```
+    pub fn uncovered_measure<Q, R>(&self, range: &R) -> Option<Q::Length>
+    where
+        K: Ord + Borrow<Q>,
+        R: RangeBounds<Q>,
+        Q: Measure + Ord + ?Sized,
+    {
+        let (start, end) = (range.start_bound(), range.end_bound());
+        let overlaps = OverlapIter::new(&self.root, start, end).map(|node| &node.key);
+
+        Gaps::new(overlaps, start, end)
+            .with_trailing_gap()
+            .try_fold(Q::Length::default(), |measure, (gap_start, gap_end)| {
+                Some(measure + Self::measure(Cut::from_start(gap_start), Cut::from_end(gap_end))?)
+            })
+    }
+
+    /// Returns the ratio of the measure of `range` covered by the tree, between `0.0` and `1.0`.
+    /// See [`IntervalTree::covered_measure`] for more details.
+    ///
+    /// Returns `None` if the measure of `range` is infinite or zero, in which case the ratio is undefined.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use unbounded_interval_tree::interval_tree::IntervalTree;
+    ///
+    /// let hour = Duration::from_secs(3600);
+    /// let tree = IntervalTree::from([hour * 9..hour * 12, hour * 13..hour * 18]);
+    ///
+    /// assert_eq!(tree.coverage_ratio(&(Duration::ZERO..hour * 24)), Some(1.0 / 3.0));
+    /// assert_eq!(tree.coverage_ratio(&(hour * 12..hour * 12)), None);
+    /// assert_eq!(tree.coverage_ratio(&(hour * 9..)), None);
+    /// ```
+    pub fn coverage_ratio<Q, R>(&self, range: &R) -> Option<f64>
+    where
+        K: Ord + Borrow<Q>,
+        R: RangeBounds<Q>,
+        Q: Measure + Ord + ?Sized,
+    {
+        let start = Cut::from_start(range.start_bound());
+        let end = Cut::from_end(range.end_bound());
+        if start >= end {
+            return None;
+        }
+
+        let whole = Self::measure(start, end)?;
+        if whole == Q::Length::default() {
+            return None;
+        }
+
+        Some(Q::ratio(&self.covered_measure(range)?, &whole))
+    }
+
+    // Returns the measure between two cuts, or `None` if it is infinite.
+    fn measure<Q>(start: Cut<&Q>, end: Cut<&Q>) -> Option<Q::Length>
+    where
+        Q: Measure + ?Sized,
+    {
+        match (start, end) {
+            (Cut::Below(start) | Cut::Above(start), Cut::Below(end) | Cut::Above(end)) => Some(Q::length(start, end)),
+            _ => None,
+        }
+    }
+
+    /// Returns the ordered list of subintervals in `range` that are not covered by the tree.
+    /// This is useful to compute what subsegments of `range` that are not covered by the intervals
+    /// stored in the tree.
+    ///
+    /// If `range` is not covered at all, this simply returns a one element vector
+    /// containing the bounds of `range`.
+    ///
+    /// The given `range` may have bounds that are of a borrowed form of the stored type `K`.
+    /// Because all the bounds returned are either from the interval tree of from the `range`, we return
+    /// references to these bounds rather than clone them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::ops::Bound::{Included, Excluded, Unbounded};
+    /// use unbounded_interval_tree::interval_tree::IntervalTree;
+    ///
+    /// let mut tree = IntervalTree::default();
+    ///
+    /// tree.insert((Included(0), Excluded(10)));
+    /// tree.insert((Excluded(10), Included(30)));
+    /// tree.insert((Excluded(50), Unbounded));
+    ///
+    /// assert_eq!(tree.get_interval_difference(&(-5..=30)),
+    ///            vec![(Included(&-5), Excluded(&0)),
+    ///                 (Included(&10), Included(&10))]);
+    /// assert_eq!(tree.get_interval_difference(&(..10)),
+    ///            vec![(Unbounded, Excluded(&0))]);
+    /// assert!(tree.get_interval_difference(&(100..)).is_empty());
+    /// ```
+    pub fn get_interval_difference<'a, Q, R>(&'a self, range: &'a R) -> Vec<Range<&'a Q>>
+    where
+        K: Ord + Borrow<Q>,
+        R: RangeBounds<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.gaps(range).collect()
+    }
+
+    /// Returns an iterator over the ordered subintervals in `range` that are not covered by the tree.
+    /// This yields the same subintervals as [`IntervalTree::get_interval_difference`], but computes
+    /// them one at a time while walking the overlapping intervals, so finding the first
+    /// uncovered subinterval does not require visiting every interval overlapping `range`.
+    ///
+    /// The given `range` may have bounds that are of a borrowed form of the stored type `K`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::ops::Bound::{Included, Excluded, Unbounded};
+    /// use unbounded_interval_tree::interval_tree::IntervalTree;
+    ///
+    /// let tree = (0..1000).map(|i| i * 10..i * 10 + 5).collect::<IntervalTree<_>>();
+    ///
+    /// let mut gaps = tree.gaps(&(12..));
+    /// assert_eq!(gaps.next(), Some((Included(&15), Excluded(&20))));
+    /// assert_eq!(gaps.next(), Some((Included(&25), Excluded(&30))));
+    ///
+    /// assert_eq!(tree.gaps(&(..=-1)).collect::<Vec<_>>(),
+    ///            vec![(Unbounded, Included(&-1))]);
+    /// ```
+    pub fn gaps<'a, Q, R>(&'a self, range: &'a R) -> impl Iterator<Item = Range<&'a Q>>
+    where
+        K: Ord + Borrow<Q>,
+        R: RangeBounds<Q>,
+        Q: Ord + ?Sized + 'a,
+    {
+        let (start, end) = (range.start_bound(), range.end_bound());
+        let overlaps = OverlapIter::new(&self.root, start, end).map(|node| &node.key);
 
-        // If q.min < first.min, we have a difference to append.
-        match (range.start_bound(), first.start_bound()) {
-            (Unbounded, Included(first_min)) => acc.push((Unbounded, Excluded(first_min.borrow()))),
-            (Unbounded, Excluded(first_min)) => acc.push((Unbounded, Included(first_min.borrow()))),
-            (Included(q_min), Included(first_min)) if q_min < first_min.borrow() => {
-                acc.push((Included(q_min), Excluded(first_min.borrow())))
-            }
-            (Excluded(q_min), Included(first_min)) if q_min < first_min.borrow() => {
-                acc.push((Excluded(q_min), Excluded(first_min.borrow())))
-            }
-            (Excluded(q_min), Excluded(first_min)) if q_min < first_min.borrow() => {
-                acc.push((Excluded(q_min), Included(first_min.borrow())))
-            }
-            (Included(q_min), Excluded(first_min)) if q_min <= first_min.borrow() => {
-                acc.push((Included(q_min), Included(first_min.borrow())))
-            }
-            _ => {}
-        };
+        Gaps::new(overlaps, start, end)
+    }
 
-        // If the max is unbounded, there can't be any difference going forward.
-        if first.1 == Unbounded {
-            return acc;
-        }
-
-        let mut contiguous = &first.1; // keeps track of the maximum of a contiguous interval.
-        for overlap in overlaps.iter().skip(1) {
-            // If contiguous < overlap.min:
-            //   1. We have a difference between contiguous -> overlap.min to fill.
-            //     1.1: Note: the endpoints of the difference appended are the opposite,
-            //          that is if contiguous was Included, then the difference must
-            //          be Excluded, and vice versa.
-            //   2. We need to update contiguous to be the new contiguous max.
-            // Note: an Included+Excluded at the same point still is contiguous!
-            match (&contiguous, &overlap.0) {
-                (Included(contiguous_max), Included(overlap_min))
-                    if contiguous_max < overlap_min =>
-                {
-                    acc.push((
-                        Excluded(contiguous_max.borrow()),
-                        Excluded(overlap_min.borrow()),
-                    ));
-                    contiguous = &overlap.1;
-                }
-                (Included(contiguous_max), Excluded(overlap_min))
-                    if contiguous_max < overlap_min =>
-                {
-                    acc.push((
-                        Excluded(contiguous_max.borrow()),
-                        Included(overlap_min.borrow()),
-                    ));
-                    contiguous = &overlap.1;
-                }
-                (Excluded(contiguous_max), Included(overlap_min))
-                    if contiguous_max < overlap_min =>
-                {
-                    acc.push((
-                        Included(contiguous_max.borrow()),
-                        Excluded(overlap_min.borrow()),
-                    ));
-                    contiguous = &overlap.1;
-                }
-                (Excluded(contiguous_max), Excluded(overlap_min))
-                    if contiguous_max <= overlap_min =>
-                {
-                    acc.push((
-                        Included(contiguous_max.borrow()),
-                        Included(overlap_min.borrow()),
-                    ));
-                    contiguous = &overlap.1;
+    /// Returns an iterator over the union of the intervals of `self` and `other`,
+    /// that is the subintervals covered by either tree.
+    ///
+    /// The subintervals are yielded in order, and they are normalized: they are disjoint,
+    /// and contiguous intervals are merged (e.g. `[1, 5)` and `[5, 9]` become `[1, 9]`).
+    /// Because all the bounds returned are from either tree, we return
+    /// references to these bounds rather than clone them.
+    /// See the [`BitOr`] implementation to get the union as a new tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::ops::Bound::{Included, Excluded, Unbounded};
+    /// use unbounded_interval_tree::interval_tree::IntervalTree;
+    ///
+    /// let a = IntervalTree::from([(Included(0), Excluded(5)), (Included(10), Included(20))]);
+    /// let b = IntervalTree::from([(Included(3), Excluded(10)), (Excluded(30), Unbounded)]);
+    ///
+    /// assert_eq!(a.union(&b).collect::<Vec<_>>(),
+    ///            vec![(Included(&0), Included(&20)),
+    ///                 (Excluded(&30), Unbounded)]);
+    /// ```
+    pub fn union<'a>(&'a self, other: &'a IntervalTree<K>) -> impl Iterator<Item = Range<&'a K>>
+    where
+        K: Ord,
+    {
+        SetOp::new(Runs::new(self.iter()), Runs::new(other.iter()), |a, b| a || b)
+    }
+
+    /// Returns an iterator over the intersection of the intervals of `self` and `other`,
+    /// that is the subintervals covered by both trees.
+    /// The subintervals are normalized, as in [`IntervalTree::union`].
+    /// See the [`BitAnd`] implementation to get the intersection as a new tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::ops::Bound::{Included, Excluded, Unbounded};
+    /// use unbounded_interval_tree::interval_tree::IntervalTree;
+    ///
+    /// let a = IntervalTree::from([(Included(0), Excluded(5)), (Included(10), Included(20))]);
+    /// let b = IntervalTree::from([(Included(3), Included(10)), (Excluded(15), Unbounded)]);
+    ///
+    /// assert_eq!(a.intersection(&b).collect::<Vec<_>>(),
+    ///            vec![(Included(&3), Excluded(&5)),
+    ///                 (Included(&10), Included(&10)),
+    ///                 (Excluded(&15), Included(&20))]);
+    /// ```
+    pub fn intersection<'a>(&'a self, other: &'a IntervalTree<K>) -> impl Iterator<Item = Range<&'a K>>
+    where
+        K: Ord,
+    {
+        SetOp::new(Runs::new(self.iter()), Runs::new(other.iter()), |a, b| a && b)
+    }
+
+    /// Returns an iterator over the difference between the intervals of `self` and `other`,
+    /// that is the subintervals covered by `self` but not by `other`.
+    /// The subintervals are normalized, as in [`IntervalTree::union`].
+    /// See the [`Sub`] implementation to get the difference as a new tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::ops::Bound::{Included, Excluded, Unbounded};
+    /// use unbounded_interval_tree::interval_tree::IntervalTree;
+    ///
+    /// let on_call = IntervalTree::from([(Included(0), Excluded(100))]);
+    /// let vacation = IntervalTree::from([(Included(20), Included(30)), (Included(90), Unbounded)]);
+    ///
+    /// assert_eq!(on_call.difference(&vacation).collect::<Vec<_>>(),
+    ///            vec![(Included(&0), Excluded(&20)),
+    ///                 (Excluded(&30), Excluded(&90))]);
+    /// ```
+    pub fn difference<'a>(&'a self, other: &'a IntervalTree<K>) -> impl Iterator<Item = Range<&'a K>>
+    where
+        K: Ord,
+    {
+        SetOp::new(Runs::new(self.iter()), Runs::new(other.iter()), |a, b| a && !b)
+    }
+
+    /// Returns an iterator over the symmetric difference between the intervals of `self` and `other`,
+    /// that is the subintervals covered by exactly one of the trees.
+    /// The subintervals are normalized, as in [`IntervalTree::union`].
+    /// See the [`BitXor`] implementation to get the symmetric difference as a new tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::ops::Bound::{Included, Excluded};
+    /// use unbounded_interval_tree::interval_tree::IntervalTree;
+    ///
+    /// let a = IntervalTree::from([(Included(0), Included(10))]);
+    /// let b = IntervalTree::from([(Included(5), Excluded(15))]);
+    ///
+    /// assert_eq!(a.symmetric_difference(&b).collect::<Vec<_>>(),
+    ///            vec![(Included(&0), Excluded(&5)),
+    ///                 (Excluded(&10), Excluded(&15))]);
+    /// ```
+    pub fn symmetric_difference<'a>(
+        &'a self,
+        other: &'a IntervalTree<K>,
+    ) -> impl Iterator<Item = Range<&'a K>>
+    where
+        K: Ord,
+    {
+        SetOp::new(Runs::new(self.iter()), Runs::new(other.iter()), |a, b| a != b)
+    }
+
+    /// Returns whether `range` is fully covered by the intervals of the tree rooted at `curr`.
+    ///
+    /// Rather than computing the difference, this follows the contiguous intervals covering
+    /// `range` from its start, and stops as soon as a point is found to be uncovered.
+    /// At every step, the interval reaching the furthest among the ones starting before the first
+    /// point not covered yet is found in a single descent, thanks to the maximum end-point
+    /// stored in every subtree. This requires no allocation.
+    pub(crate) fn covers<V, A, Q, R>(curr: &Option<Box<Node<K, V, A>>>, range: &R) -> bool
+    where
+        K: Ord + Borrow<Q>,
+        R: RangeBounds<Q>,
+        Q: Ord + ?Sized,
+    {
+        // The start of the part of `range` that is not known to be covered yet.
+        let mut uncovered = range.start_bound();
+
+        loop {
+            let mut contiguous: Option<&Bound<K>> = None;
+            let mut next = curr;
+
+            // Find the maximum end-point of the intervals starting no later than `uncovered`.
+            while let Some(node) = next {
+                if Self::is_startbound_greater(&node.key.0, uncovered) {
+                    next = &node.left;
+                    continue;
                 }
-                _ => {}
-            }
-
-            // If contiguous.max < overlap.max, we set contiguous to the new max.
-            match (&contiguous, &overlap.1) {
-                (_, Unbounded) => return acc,
-                (Included(contiguous_max), Included(overlap_max))
-                | (Excluded(contiguous_max), Excluded(overlap_max))
-                | (Included(contiguous_max), Excluded(overlap_max))
-                    if contiguous_max < overlap_max =>
-                {
-                    contiguous = &overlap.1
+
+                // All the intervals in the left subtree start before this node.
+                let left_max = node.left.as_ref().map(|left| &left.value);
+                for max in [Some(&node.key.1), left_max].into_iter().flatten() {
+                    let extends = match contiguous {
+                        Some(contiguous) => Self::cmp_endbound(contiguous, max) == Less,
+                        None => true,
+                    };
+                    if extends {
+                        contiguous = Some(max);
+                    }
                 }
-                (Excluded(contiguous_max), Included(overlap_max))
-                    if contiguous_max <= overlap_max =>
-                {
-                    contiguous = &overlap.1
+                next = &node.right;
+            }
+
+            // If contiguous.max < uncovered.min, the start of `uncovered` is not covered.
+            let contiguous = match contiguous {
+                Some(contiguous) if !Self::is_endbound_before(contiguous, &(uncovered, Unbounded)) => {
+                    contiguous
                 }
-                _ => {}
+                _ => return false,
             };
-        }
 
-        // If contiguous.max < q.max, we have a difference to append.
-        match (&contiguous, range.end_bound()) {
-            (Included(contiguous_max), Included(q_max)) if contiguous_max.borrow() < q_max => {
-                acc.push((Excluded(contiguous_max.borrow()), Included(q_max)))
-            }
-            (Included(contiguous_max), Excluded(q_max)) if contiguous_max.borrow() < q_max => {
-                acc.push((Excluded(contiguous_max.borrow()), Excluded(q_max)))
-            }
-            (Excluded(contiguous_max), Excluded(q_max)) if contiguous_max.borrow() < q_max => {
-                acc.push((Included(contiguous_max.borrow()), Excluded(q_max)))
-            }
-            (Excluded(contiguous_max), Included(q_max)) if contiguous_max.borrow() <= q_max => {
-                acc.push((Included(contiguous_max.borrow()), Included(q_max)))
+            // If contiguous.max >= q.max, the rest of `range` is covered.
+            if !Self::is_endbound_less(contiguous, range.end_bound()) {
+                return true;
             }
-            _ => {}
-        };
 
-        acc
+            // The bound following contiguous.max is the opposite one,
+            // as an Included+Excluded at the same point still is contiguous.
+            uncovered = match contiguous {
+                Included(x) => Excluded(x.borrow()),
+                Excluded(x) => Included(x.borrow()),
+                Unbounded => unreachable!("an unbounded end-point covers the rest of the range"),
+            };
+        }
+    }
+
+    /// Returns a node of the tree rooted at `curr` whose interval overlaps with the interval
+    /// between the cuts `start` and `end`. If `touching`, a node whose interval is adjacent
+    /// to it can be returned as well.
+    ///
+    /// The intervals of the tree must be disjoint, so that they are sorted by their end-point
+    /// as well as by their start-point, and a single descent is enough.
+    pub(crate) fn find_disjoint<'a, V>(
+        curr: &'a Option<Box<Node<K, V>>>,
+        start: Cut<&K>,
+        end: Cut<&K>,
+        touching: bool,
+    ) -> Option<&'a Node<K, V>>
+    where
+        K: Ord,
+    {
+        let mut curr = curr;
+
+        while let Some(node) = curr {
+            let node_start = Cut::from_start(node.key.0.as_ref());
+            let node_end = Cut::from_end(node.key.1.as_ref());
+
+            let (is_before, is_after) = if touching {
+                (node_end < start, node_start > end)
+            } else {
+                (node_end <= start, node_start >= end)
+            };
+
+            curr = if is_before {
+                &node.right
+            } else if is_after {
+                &node.left
+            } else {
+                return Some(node);
+            };
+        }
+
+        None
     }
 
-    fn get_interval_overlaps_rec<'a, Q, R>(
-        curr: &'a Option<Box<Node<K>>>,
-        range: &R,
-        acc: &mut Vec<&'a Range<K>>,
-    ) where
+    // Returns whether the start-point `min` is greater than the start-point `start`.
+    fn is_startbound_greater<Q>(min: &Bound<K>, start: Bound<&Q>) -> bool
+    where
         K: Ord + Borrow<Q>,
-        R: RangeBounds<Q>,
         Q: Ord + ?Sized,
     {
-        // If we reach None, stop recursing along this subtree.
-        let node = match curr {
-            None => return,
-            Some(node) => node,
+        // Same ordering as in `fn cmp` below, where Unbounded is the smallest start-point.
+        let min = match min {
+            Included(x) => Some((x.borrow(), 1)),
+            Excluded(x) => Some((x.borrow(), 2)),
+            Unbounded => None,
+        };
+        let start = match start {
+            Included(x) => Some((x, 1)),
+            Excluded(x) => Some((x, 2)),
+            Unbounded => None,
+        };
+        min > start
+    }
+
+    // Returns whether the end-point `max` is smaller than the end-point `end`.
+    fn is_endbound_less<Q>(max: &Bound<K>, end: Bound<&Q>) -> bool
+    where
+        K: Ord + Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        // Same ordering as in `fn cmp_endbound` below, where Unbounded is the greatest end-point.
+        let max = match max {
+            Included(x) => Some((x.borrow(), 2)),
+            Excluded(x) => Some((x.borrow(), 1)),
+            Unbounded => None,
+        };
+        let end = match end {
+            Included(x) => Some((x, 2)),
+            Excluded(x) => Some((x, 1)),
+            Unbounded => None,
         };
+        match (max, end) {
+            (Some(max), Some(end)) => max < end,
+            (Some(_), None) => true,
+            _ => false,
+        }
+    }
 
-        // See if subtree.max < q.min. If that is the case, there is no point
-        // in visiting the rest of the subtree (we know that the rest of the intervals
-        // will necessarily be smaller than `q`).
+    // Returns whether the end-point `max` is smaller than q.min, where `q` is `range`.
+    fn is_endbound_before<Q, R>(max: &Bound<K>, range: &R) -> bool
+    where
+        K: Ord + Borrow<Q>,
+        R: RangeBounds<Q>,
+        Q: Ord + ?Sized,
+    {
         // ~ Recall the ordering rules (as defined in `fn cmp` below). ~
-        // -> If subtree.max is Unbounded, subtree.max < q.min is impossible.
-        // -> If q.min is Unbounded, subtree.max < q.min is impossible.
+        // -> If max is Unbounded, max < q.min is impossible.
+        // -> If q.min is Unbounded, max < q.min is impossible.
         // -> If they are equal, we have 4 cases:
-        //  * subtree.max: Included(x) / q.min: Included(x) -> =, we keep visiting the subtree
-        //  * subtree.max: Included(x) / q.min: Excluded(x) -> <, condition satisfied
-        //  * subtree.max: Excluded(x) / q.min: Included(x) -> <, condition satisfied
-        //  * subtree.max: Excluded(x) / q.min: Excluded(x) -> <, condition satisfied
-        let max_subtree = match &node.value {
+        //  * max: Included(x) / q.min: Included(x) -> =, condition not satisfied
+        //  * max: Included(x) / q.min: Excluded(x) -> <, condition satisfied
+        //  * max: Excluded(x) / q.min: Included(x) -> <, condition satisfied
+        //  * max: Excluded(x) / q.min: Excluded(x) -> <, condition satisfied
+        let max = match max {
             Included(x) => Some((x.borrow(), 2)),
             Excluded(x) => Some((x.borrow(), 1)),
             Unbounded => None,
@@ -519,34 +2034,27 @@ impl<K> IntervalTree<K> {
             Excluded(x) => Some((x, 3)),
             Unbounded => None,
         };
-        match (max_subtree, min_q) {
-            (Some(max_subtree), Some(min_q)) if max_subtree < min_q => return,
-            _ => {}
-        };
-
-        // Search left subtree.
-        Self::get_interval_overlaps_rec(&node.left, range, acc);
+        match (max, min_q) {
+            (Some(max), Some(min_q)) => max < min_q,
+            _ => false,
+        }
+    }
 
-        // Visit this node.
-        // If node.min <= q.max AND node.max >= q.min, we have an intersection.
-        // Let's start with the first inequality, node.min <= q.max.
-        // -> If node.min is Unbounded, node.min <= q.max is a tautology.
-        // -> If q.max is Unbounded, node.min <= q.max is a tautology.
+    // Returns whether the start-point `min` is greater than q.max, where `q` is `range`.
+    fn is_startbound_after<Q, R>(min: &Bound<K>, range: &R) -> bool
+    where
+        K: Ord + Borrow<Q>,
+        R: RangeBounds<Q>,
+        Q: Ord + ?Sized,
+    {
+        // -> If min is Unbounded, min > q.max is impossible.
+        // -> If q.max is Unbounded, min > q.max is impossible.
         // -> If they are equal, we have 4 cases:
-        //  * node.min: Included(x) / q.max: Included(x) -> =, we go to 2nd inequality
-        //  * node.min: Included(x) / q.max: Excluded(x) -> >, 1st inequality not satisfied
-        //  * node.min: Excluded(x) / q.max: Included(x) -> >, 1st inequality not satisfied
-        //  * node.min: Excluded(x) / q.max: Excluded(x) -> >, 1st inequality not satisfied
-        //
-        // Notice that after we visit the node, we should visit the right subtree. However,
-        // if node.min > q.max, we can skip right visiting the right subtree.
-        // -> If node.min is Unbounded, node.min > q.max is impossible.
-        // -> If q.max is Unbounded, node.min > q.max is impossible.
-        //
-        // It just so happens that we already do this check in the match to satisfy
-        // the previous first condition. Hence, we decided to add an early return
-        // in there, rather than repeat the logic afterwards.
-        let min_node = match &node.key.0 {
+        //  * min: Included(x) / q.max: Included(x) -> =, condition not satisfied
+        //  * min: Included(x) / q.max: Excluded(x) -> >, condition satisfied
+        //  * min: Excluded(x) / q.max: Included(x) -> >, condition satisfied
+        //  * min: Excluded(x) / q.max: Excluded(x) -> >, condition satisfied
+        let min = match min {
             Included(x) => Some((x.borrow(), 2)),
             Excluded(x) => Some((x.borrow(), 3)),
             Unbounded => None,
@@ -556,35 +2064,149 @@ impl<K> IntervalTree<K> {
             Excluded(x) => Some((x, 1)),
             Unbounded => None,
         };
-        match (min_node, max_q) {
-            // If the following condition is met, we do not have an intersection.
-            // On top of that, we know that we can skip visiting the right subtree,
-            // so we can return eagerly.
-            (Some(min_node), Some(max_q)) if min_node > max_q => return,
-            _ => {
-                // Now we are at the second inequality, node.max >= q.min.
-                // -> If node.max is Unbounded, node.max >= q.min is a tautology.
-                // -> If q.min is Unbounded, node.max >= q.min is a tautology.
-                // -> If they are equal, we have 4 cases:
-                //  * node.max: Included(x) / q.min: Included(x) -> =, 2nd inequality satisfied
-                //  * node.max: Included(x) / q.min: Excluded(x) -> <, 2nd inequality not satisfied
-                //  * node.max: Excluded(x) / q.min: Included(x) -> <, 2nd inequality not satisfied
-                //  * node.max: Excluded(x) / q.min: Excluded(x) -> <, 2nd inequality not satisfied
-                let max_node = match &node.key.1 {
-                    Included(x) => Some((x.borrow(), 2)),
-                    Excluded(x) => Some((x.borrow(), 1)),
-                    Unbounded => None,
-                };
+        match (min, max_q) {
+            (Some(min), Some(max_q)) => min > max_q,
+            _ => false,
+        }
+    }
+
+    /// Removes the interval `range` from the tree, and returns it if it was stored in the tree.
+    ///
+    /// The returned value will be `None` if no interval in the tree is equal to `range`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::ops::Bound::{Included, Excluded, Unbounded};
+    /// use unbounded_interval_tree::interval_tree::IntervalTree;
+    ///
+    /// let mut tree = IntervalTree::default();
+    ///
+    /// tree.insert((Included(5), Excluded(9)));
+    /// tree.insert((Unbounded, Included(10)));
+    /// tree.insert(20..30);
+    ///
+    /// assert_eq!(tree.remove(&(..=10)), Some((Unbounded, Included(10))));
+    /// assert!(!tree.contains_point(&10));
+    /// assert!(tree.contains_point(&6));
+    /// assert_eq!(tree.len(), 2);
+    ///
+    /// // Only exact matches are removed.
+    /// assert_eq!(tree.remove(&(5..=9)), None);
+    /// assert_eq!(tree.len(), 2);
+    /// ```
+    pub fn remove<R>(&mut self, range: &R) -> Option<Range<K>>
+    where
+        K: Ord + Clone,
+        R: RangeBounds<K>,
+    {
+        let range = (range.start_bound().cloned(), range.end_bound().cloned());
+        let (removed, _) = Self::remove_rec(&mut self.root, &range)?;
+
+        Self::remove_end(&mut self.ends, &removed);
+        self.size -= 1;
+        Some(removed)
+    }
+
+    // Adds the interval `range` to the index of end-points rooted at `ends`.
+    fn insert_end(ends: &mut Option<Box<Node<K, (), Size>>>, range: Range<K>)
+    where
+        K: Ord + Clone,
+    {
+        Self::insert_rec_by(ends, range, (), Self::cmp_by_end);
+    }
+
+    // Removes the interval `range` from the index of end-points rooted at `ends`.
+    fn remove_end(ends: &mut Option<Box<Node<K, (), Size>>>, range: &Range<K>)
+    where
+        K: Ord + Clone,
+    {
+        Self::remove_rec_by(ends, range, Self::cmp_by_end);
+    }
+
+    /// Removes `range` from the given subtree, and rebalances it.
+    /// Returns the removed range along with its associated data.
+    pub(crate) fn remove_rec<V, A>(
+        curr: &mut Option<Box<Node<K, V, A>>>,
+        range: &Range<K>,
+    ) -> Option<(Range<K>, V)>
+    where
+        K: Ord + Clone,
+        A: Augment<K, V>,
+    {
+        Self::remove_rec_by(curr, range, Self::cmp)
+    }
 
-                match (max_node, min_q) {
-                    (Some(max_node), Some(min_q)) if max_node < min_q => {}
-                    _ => acc.push(&node.key),
+    // Same as `remove_rec`, in a subtree sorted by `cmp` rather than by `IntervalTree::cmp`.
+    fn remove_rec_by<V, A>(
+        curr: &mut Option<Box<Node<K, V, A>>>,
+        range: &Range<K>,
+        cmp: fn(&Range<K>, &Range<K>) -> Ordering,
+    ) -> Option<(Range<K>, V)>
+    where
+        K: Ord + Clone,
+        A: Augment<K, V>,
+    {
+        let node = curr.as_mut()?;
+
+        let removed = match cmp(&node.key, range) {
+            Less => Self::remove_rec_by(&mut node.right, range, cmp)?,
+            Greater => Self::remove_rec_by(&mut node.left, range, cmp)?,
+            Equal => {
+                let mut node = curr.take().unwrap();
+
+                // A node with at most one child is simply replaced by that child.
+                // Otherwise, the node takes the key of its inorder successor
+                // (the minimum of its right subtree), which is removed instead.
+                let removed = match (node.left.take(), node.right.take()) {
+                    (None, None) => (node.key, node.data),
+                    (Some(child), None) | (None, Some(child)) => {
+                        *curr = Some(child);
+                        (node.key, node.data)
+                    }
+                    (Some(left), Some(right)) => {
+                        let mut right = Some(right);
+                        let (successor_key, successor_data) = Self::remove_min(&mut right).unwrap();
+
+                        node.left = Some(left);
+                        node.right = right;
+                        let removed = (
+                            mem::replace(&mut node.key, successor_key),
+                            mem::replace(&mut node.data, successor_data),
+                        );
+                        Node::rebalance(&mut node);
+                        *curr = Some(node);
+                        removed
+                    }
                 };
+
+                return Some(removed);
             }
         };
 
-        // Search right subtree.
-        Self::get_interval_overlaps_rec(&node.right, range, acc);
+        // A node was removed from one of the subtrees, so the max end-point
+        // stored in the current node might have to be lowered, and the subtree
+        // might have to be rotated.
+        Node::rebalance(node);
+        Some(removed)
+    }
+
+    fn remove_min<V, A>(curr: &mut Option<Box<Node<K, V, A>>>) -> Option<(Range<K>, V)>
+    where
+        K: Ord + Clone,
+        A: Augment<K, V>,
+    {
+        let node = curr.as_mut()?;
+
+        if node.left.is_some() {
+            let min = Self::remove_min(&mut node.left);
+            Node::rebalance(node);
+            return min;
+        }
+
+        let mut node = curr.take().unwrap();
+        *curr = node.right.take();
+        Some((node.key, node.data))
     }
 
     /// Removes a random leaf from the tree,
@@ -622,130 +2244,29 @@ impl<K> IntervalTree<K> {
     where
         K: Ord + Clone,
     {
-        use rand::random;
-
-        // If interval tree is empty, just return None.
-        if self.root.is_none() {
-            return None;
-        }
-
-        self.size -= 1;
-
-        let mut curr = self.root.as_mut().unwrap();
-
-        // If we only have one node, delete it right away.
-        if curr.left.is_none() && curr.right.is_none() {
-            let root = mem::take(&mut self.root).unwrap();
-            return Some(root.key);
-        }
-
-        // Keep track of visited nodes, because we will need to walk up
-        // the tree after deleting the leaf in order to possibly update
-        // their value stored.
-        // The first element of the tuple is a &mut to the value of the node,
-        // whilst the second element is the new potential value to store, based
-        // on the non-visited path (recall that this is a BST). It
-        // is very much possible that both elements are equal: that would imply that the
-        // current value depends solely on the non-visited path, hence the deleted
-        // node will have no impact up the tree, at least from the current point.
-        let mut path: Vec<(_, _)> = Vec::new();
-
-        // Used to keep track of the direction taken from a node.
-        enum Direction {
-            LEFT,
-            RIGHT,
-        }
-
-        // Traverse the tree until we find a leaf.
-        let (deleted, new_max) = loop {
-            // Note that at this point in the loop, `curr` can't be a leaf.
-            // Indeed, we traverse the tree such that `curr` is always an
-            // internal node, so that it is easy to replace a leaf from `curr`.
-            let direction = if curr.left.is_none() {
-                Direction::RIGHT
-            } else if curr.right.is_none() {
-                Direction::LEFT
-            } else if random() {
-                Direction::LEFT
-            } else {
-                Direction::RIGHT
-            };
-            // End-bound of the current node.
-            let curr_end = &curr.key.1;
-
-            // LEFT and RIGHT paths are somewhat repetitive, but this way
-            // was the only way to satisfy the borrowchecker...
-            match direction {
-                Direction::LEFT => {
-                    // If we go left and the right path is `None`,
-                    // then the right path has no impact towards
-                    // the value stored by the current node.
-                    // Otherwise, the current node's value might change
-                    // to the other branch's max value once we remove the
-                    // leaf, so let's keep track of that.
-                    let max_other = if curr.right.is_none() {
-                        curr_end
-                    } else {
-                        let other_value = &curr.right.as_ref().unwrap().value;
-                        match Self::cmp_endbound(curr_end, other_value) {
-                            Greater | Equal => curr_end,
-                            Less => other_value,
-                        }
-                    };
-
-                    // Check if the next node is a leaf. If it is, then we want to
-                    // stop traversing, and remove the leaf.
-                    let next = curr.left.as_ref().unwrap();
-                    if next.is_leaf() {
-                        curr.value = max_other.clone();
-                        break (mem::take(&mut curr.left).unwrap(), max_other);
-                    }
-
-                    // If the next node is *not* a leaf, then we can update the visited path
-                    // with the current values, and move on to the next node.
-                    path.push((&mut curr.value, max_other));
-                    curr = curr.left.as_mut().unwrap();
-                }
-                Direction::RIGHT => {
-                    let max_other = if curr.left.is_none() {
-                        curr_end
-                    } else {
-                        let other_value = &curr.left.as_ref().unwrap().value;
-                        match Self::cmp_endbound(curr_end, other_value) {
-                            Greater | Equal => curr_end,
-                            Less => other_value,
-                        }
-                    };
-
-                    let next = curr.right.as_ref().unwrap();
-                    if next.is_leaf() {
-                        curr.value = max_other.clone();
-                        break (mem::take(&mut curr.right).unwrap(), max_other);
-                    }
-
-                    path.push((&mut curr.value, max_other));
-                    curr = curr.right.as_mut().unwrap();
-                }
-            };
-        };
+        let leaf = Self::get_random_leaf(&self.root)?.clone();
+        self.remove(&leaf)
+    }
 
-        // We have removed the leaf. Now, we bubble-up the visited path.
-        // If the removed node's value impacted its ancestors, then we update
-        // the ancestors' value so that they store the new max value in their
-        // respective subtree.
-        while let Some((value, max_other)) = path.pop() {
-            if Self::cmp_endbound(value, max_other) == Equal {
-                break;
-            }
+    /// Walks down the given subtree, choosing a random direction when both
+    /// children are non-empty, and returns the key of the leaf reached.
+    pub(crate) fn get_random_leaf<V, A>(curr: &Option<Box<Node<K, V, A>>>) -> Option<&Range<K>> {
+        use rand::random;
 
-            match Self::cmp_endbound(value, new_max) {
-                Equal => break,
-                Greater => *value = new_max.clone(),
-                Less => unreachable!("Can't have a new max that is bigger"),
+        let mut curr = curr.as_ref()?;
+        loop {
+            curr = match (&curr.left, &curr.right) {
+                (None, None) => return Some(&curr.key),
+                (Some(next), None) | (None, Some(next)) => next,
+                (Some(left), Some(right)) => {
+                    if random() {
+                        left
+                    } else {
+                        right
+                    }
+                }
             };
         }
-
-        Some(deleted.key.clone())
     }
 
     /// Returns the number of ranges stored in the interval tree.
@@ -789,6 +2310,32 @@ impl<K> IntervalTree<K> {
         self.len() == 0
     }
 
+    /// Returns the height of the interval tree, that is the number of nodes
+    /// on the longest path from the root to a leaf.
+    ///
+    /// Because the tree is kept balanced, its height is at most `1.44 * log2(n + 2)`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unbounded_interval_tree::interval_tree::IntervalTree;
+    ///
+    /// let mut tree = IntervalTree::default();
+    ///
+    /// assert_eq!(tree.height(), 0);
+    ///
+    /// // Sorted insertions do not degenerate the tree into a linked list.
+    /// for i in 0..1000 {
+    ///     tree.insert(i..i + 1);
+    /// }
+    ///
+    /// assert_eq!(tree.len(), 1000);
+    /// assert!(tree.height() <= 14);
+    /// ```
+    pub fn height(&self) -> usize {
+        Node::height(&self.root)
+    }
+
     /// Clear the interval tree, removing all values stored.
     ///
     /// # Examples
@@ -805,11 +2352,32 @@ impl<K> IntervalTree<K> {
     /// assert!(tree.is_empty());
     /// ```
     pub fn clear(&mut self) {
-        self.root = None;
+        Node::drop_tree(self.root.take());
+        Node::drop_tree(self.ends.take());
         self.size = 0;
     }
 
-    fn cmp(r1: &Range<K>, r2: &Range<K>) -> Ordering
+    /// Checks that the start bound of `range` is smaller or equal to its end bound,
+    /// and that `range` contains at least one point.
+    pub(crate) fn validate(range: &Range<K>) -> Result<(), IntervalError>
+    where
+        K: Ord,
+    {
+        let (start, end) = match range {
+            (Included(start) | Excluded(start), Included(end) | Excluded(end)) => (start, end),
+            // An unbounded interval always contains some points.
+            _ => return Ok(()),
+        };
+
+        match start.cmp(end) {
+            Greater => Err(IntervalError::Inverted),
+            // Only [x, x] contains a point, as ]x, x], [x, x[ and ]x, x[ are all empty.
+            Equal if !matches!(range, (Included(_), Included(_))) => Err(IntervalError::Empty),
+            _ => Ok(()),
+        }
+    }
+
+    pub(crate) fn cmp(r1: &Range<K>, r2: &Range<K>) -> Ordering
     where
         K: Ord,
     {
@@ -852,7 +2420,7 @@ impl<K> IntervalTree<K> {
         Self::cmp_endbound(&r1.1, &r2.1)
     }
 
-    fn cmp_endbound(e1: &Bound<K>, e2: &Bound<K>) -> Ordering
+    pub(crate) fn cmp_endbound(e1: &Bound<K>, e2: &Bound<K>) -> Ordering
     where
         K: Ord,
     {
@@ -877,37 +2445,573 @@ impl<K> IntervalTree<K> {
             (Some(r1), Some(ref r2)) => r1.cmp(r2),
         }
     }
+
+    // Compares intervals by end-point, then by start-point, as in the index of end-points.
+    fn cmp_by_end(r1: &Range<K>, r2: &Range<K>) -> Ordering
+    where
+        K: Ord,
+    {
+        Self::cmp_endbound(&r1.1, &r2.1).then_with(|| Self::cmp(r1, r2))
+    }
 }
 
 /// An inorder interator through the interval tree.
 pub struct IntervalTreeIter<'a, K> {
-    to_visit: Vec<&'a Box<Node<K>>>,
-    curr: &'a Option<Box<Node<K>>>,
+    inner: NodeIter<'a, K, (), Size>,
 }
 
 impl<'a, K> Iterator for IntervalTreeIter<'a, K> {
     type Item = &'a Range<K>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.curr.is_none() && self.to_visit.is_empty() {
+        self.inner.next().map(|node| &node.key)
+    }
+}
+
+type SweepCut<'a, K, Q> = (Cut<&'a Q>, bool, &'a Range<K>);
+
+/// A sweep through the intervals overlapping with a range, clipped to that range, splitting it
+/// into its elementary segments: the points of a segment are all contained in the same intervals.
+struct Sweep<'a, K, Q: ?Sized> {
+    // Cuts at which an interval enters (`true`) or exits (`false`) the sweep, in order.
+    cuts: Peekable<vec::IntoIter<SweepCut<'a, K, Q>>>,
+    // Start cut of the next segment, and end cut of the range.
+    cut: Cut<&'a Q>,
+    end: Cut<&'a Q>,
+    // Inorder list of the intervals containing the next segment.
+    covering: Vec<&'a Range<K>>,
+}
+
+impl<'a, K, Q> Sweep<'a, K, Q>
+where
+    K: Ord + Borrow<Q>,
+    Q: Ord + ?Sized,
+{
+    fn new<V, A, R>(root: &'a Option<Box<Node<K, V, A>>>, range: &'a R) -> Self
+    where
+        R: RangeBounds<Q>,
+    {
+        let start = Cut::from_start(range.start_bound());
+        let end = Cut::from_end(range.end_bound());
+
+        let mut cuts = vec![];
+        // An empty or inverted range has no segment.
+        if start < end {
+            for node in OverlapIter::new(root, range.start_bound(), range.end_bound()) {
+                let node_start = Cut::from_start(node.key.0.as_ref().map(Borrow::borrow));
+                let node_end = Cut::from_end(node.key.1.as_ref().map(Borrow::borrow));
+                cuts.push((node_start.max(start), true, &node.key));
+                cuts.push((node_end.min(end), false, &node.key));
+            }
+        }
+        cuts.sort_by_key(|(cut, _, _)| *cut);
+
+        Sweep {
+            cuts: cuts.into_iter().peekable(),
+            cut: start,
+            end,
+            covering: vec![],
+        }
+    }
+
+    // Returns the next segment, along with the intervals containing it.
+    fn next_segment(&mut self) -> Option<(Range<&'a Q>, &[&'a Range<K>])> {
+        if self.cut >= self.end {
             return None;
         }
 
-        while self.curr.is_some() {
-            self.to_visit.push(self.curr.as_ref().unwrap());
-            self.curr = &self.curr.as_ref().unwrap().left;
+        // The intervals exiting at this cut were all entered before it, so the order
+        // in which the intervals entering and exiting at the same cut are handled does not matter.
+        while let Some((_, is_start, key)) = self.cuts.next_if(|(next, _, _)| *next == self.cut) {
+            let position = self.covering.binary_search_by(|covering| IntervalTree::cmp(covering, key));
+            match (is_start, position) {
+                (true, Err(position)) => self.covering.insert(position, key),
+                (false, Ok(position)) => {
+                    self.covering.remove(position);
+                }
+                _ => unreachable!("intervals are stored once, and exit after they enter"),
+            }
+        }
+
+        let next = self.cuts.peek().map_or(self.end, |(next, _, _)| *next);
+        let segment = (self.cut.into_start(), next.into_end());
+        self.cut = next;
+
+        Some((segment, &self.covering))
+    }
+}
+
+/// An interval ordered by its end bound, then by the order of [`IntervalTree::cmp`].
+struct ByEnd<'a, K>(&'a Range<K>);
+
+impl<'a, K: Ord> Ord for ByEnd<'a, K> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        Cut::from_end(self.0 .1.as_ref())
+            .cmp(&Cut::from_end(other.0 .1.as_ref()))
+            .then_with(|| IntervalTree::cmp(self.0, other.0))
+    }
+}
+
+impl<'a, K: Ord> PartialOrd for ByEnd<'a, K> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<'a, K: Ord> PartialEq for ByEnd<'a, K> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Equal
+    }
+}
+
+impl<'a, K: Ord> Eq for ByEnd<'a, K> {}
+
+/// An inorder iterator through the nodes overlapping with a range, generic over the
+/// data and the aggregate stored in the nodes so that it can be shared by all the trees.
+pub(crate) struct OverlapIter<'a, 'b, K, V, Q: ?Sized, A = ()> {
+    // Nodes whose left subtree is being visited, and that still have to be
+    // visited along with their right subtree.
+    to_visit: Vec<&'a Node<K, V, A>>,
+    range: (Bound<&'b Q>, Bound<&'b Q>),
+}
+
+impl<'a, 'b, K, V, Q, A> OverlapIter<'a, 'b, K, V, Q, A>
+where
+    K: Ord + Borrow<Q>,
+    Q: Ord + ?Sized,
+{
+    pub(crate) fn new(root: &'a Option<Box<Node<K, V, A>>>, start: Bound<&'b Q>, end: Bound<&'b Q>) -> Self {
+        let mut iter = OverlapIter {
+            to_visit: Vec::new(),
+            range: (start, end),
+        };
+
+        iter.push_left_branch(root);
+        iter
+    }
+
+    fn push_left_branch(&mut self, mut curr: &'a Option<Box<Node<K, V, A>>>) {
+        while let Some(node) = curr {
+            // See if subtree.max < q.min. If that is the case, there is no point
+            // in visiting the rest of the subtree (we know that the rest of the intervals
+            // will necessarily be smaller than `q`).
+            if IntervalTree::is_endbound_before(&node.value, &self.range) {
+                return;
+            }
+
+            self.to_visit.push(node);
+            curr = &node.left;
+        }
+    }
+}
+
+impl<'a, 'b, K, V, Q, A> Iterator for OverlapIter<'a, 'b, K, V, Q, A>
+where
+    K: Ord + Borrow<Q>,
+    Q: Ord + ?Sized,
+{
+    type Item = &'a Node<K, V, A>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(node) = self.to_visit.pop() {
+            // If node.min > q.max, neither the right subtree nor the nodes left to visit
+            // can overlap with `q`, as they all start after this node.
+            if IntervalTree::is_startbound_after(&node.key.0, &self.range) {
+                self.to_visit.clear();
+                return None;
+            }
+
+            self.push_left_branch(&node.right);
+
+            // If node.min <= q.max AND node.max >= q.min, we have an intersection.
+            if !IntervalTree::is_endbound_before(&node.key.1, &self.range) {
+                return Some(node);
+            }
+        }
+
+        None
+    }
+}
+
+/// An inorder iterator through the nodes whose start cut lies within `starts`, and whose end cut
+/// is not below `min_end`. Like [`OverlapIter`], subtrees that cannot hold any such node are skipped,
+/// using both the start ordering and the maximum end of the nodes.
+struct PrunedIter<'a, 'b, K, V, Q: ?Sized, A = ()> {
+    // Nodes whose left subtree is being visited, and that still have to be
+    // visited along with their right subtree.
+    to_visit: Vec<&'a Node<K, V, A>>,
+    starts: (Bound<Cut<&'b Q>>, Bound<Cut<&'b Q>>),
+    min_end: Cut<&'b Q>,
+}
+
+impl<'a, 'b, K, V, Q, A> PrunedIter<'a, 'b, K, V, Q, A>
+where
+    K: Ord + Borrow<Q>,
+    Q: Ord + ?Sized,
+{
+    fn new(
+        root: &'a Option<Box<Node<K, V, A>>>,
+        starts: (Bound<Cut<&'b Q>>, Bound<Cut<&'b Q>>),
+        min_end: Cut<&'b Q>,
+    ) -> Self {
+        let mut iter = PrunedIter {
+            to_visit: Vec::new(),
+            starts,
+            min_end,
+        };
+
+        iter.push_left_branch(root);
+        iter
+    }
+
+    fn push_left_branch(&mut self, mut curr: &'a Option<Box<Node<K, V, A>>>) {
+        while let Some(node) = curr {
+            // If subtree.max < min_end, none of the nodes of the subtree end late enough.
+            if Cut::from_end(node.value.as_ref().map(Borrow::borrow)) < self.min_end {
+                return;
+            }
+
+            self.to_visit.push(node);
+
+            // The nodes of the left subtree start before this node, so if it starts
+            // too early, so do they.
+            let start = Cut::from_start(node.key.0.as_ref().map(Borrow::borrow));
+            if !self.starts_late_enough(start) {
+                return;
+            }
+            curr = &node.left;
+        }
+    }
+
+    fn starts_late_enough(&self, start: Cut<&Q>) -> bool {
+        match self.starts.0 {
+            Included(min) => min <= start,
+            Excluded(min) => min < start,
+            Unbounded => true,
+        }
+    }
+
+    fn starts_early_enough(&self, start: Cut<&Q>) -> bool {
+        match self.starts.1 {
+            Included(max) => start <= max,
+            Excluded(max) => start < max,
+            Unbounded => true,
+        }
+    }
+}
+
+impl<'a, 'b, K, V, Q, A> Iterator for PrunedIter<'a, 'b, K, V, Q, A>
+where
+    K: Ord + Borrow<Q>,
+    Q: Ord + ?Sized,
+{
+    type Item = &'a Node<K, V, A>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(node) = self.to_visit.pop() {
+            let start = Cut::from_start(node.key.0.as_ref().map(Borrow::borrow));
+            let end = Cut::from_end(node.key.1.as_ref().map(Borrow::borrow));
+
+            // Neither the right subtree nor the nodes left to visit start before this node,
+            // so if it starts too late, so do they.
+            if !self.starts_early_enough(start) {
+                self.to_visit.clear();
+                return None;
+            }
+
+            self.push_left_branch(&node.right);
+
+            if self.starts_late_enough(start) && self.min_end <= end {
+                return Some(node);
+            }
+        }
+
+        None
+    }
+}
+
+/// Mutable counterpart of [`OverlapIter`], giving mutable access to the data of the nodes.
+pub(crate) struct OverlapIterMut<'a, 'b, K, V, Q: ?Sized> {
+    to_visit: Vec<SplitNode<'a, K, V>>,
+    range: (Bound<&'b Q>, Bound<&'b Q>),
+}
+
+impl<'a, 'b, K, V, Q> OverlapIterMut<'a, 'b, K, V, Q>
+where
+    K: Ord + Borrow<Q>,
+    Q: Ord + ?Sized,
+{
+    pub(crate) fn new(root: &'a mut Option<Box<Node<K, V>>>, start: Bound<&'b Q>, end: Bound<&'b Q>) -> Self {
+        let mut iter = OverlapIterMut {
+            to_visit: Vec::new(),
+            range: (start, end),
+        };
+
+        iter.push_left_branch(root);
+        iter
+    }
+
+    fn push_left_branch(&mut self, mut curr: &'a mut Option<Box<Node<K, V>>>) {
+        while let Some(node) = curr {
+            if IntervalTree::is_endbound_before(&node.value, &self.range) {
+                return;
+            }
+
+            let Node {
+                key,
+                data,
+                left,
+                right,
+                ..
+            } = &mut **node;
+            self.to_visit.push((key, data, right));
+            curr = left;
+        }
+    }
+}
+
+impl<'a, 'b, K, V, Q> Iterator for OverlapIterMut<'a, 'b, K, V, Q>
+where
+    K: Ord + Borrow<Q>,
+    Q: Ord + ?Sized,
+{
+    type Item = (&'a Range<K>, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((key, data, right)) = self.to_visit.pop() {
+            if IntervalTree::is_startbound_after(&key.0, &self.range) {
+                self.to_visit.clear();
+                return None;
+            }
+
+            self.push_left_branch(right);
+
+            if !IntervalTree::is_endbound_before(&key.1, &self.range) {
+                return Some((key, data));
+            }
+        }
+
+        None
+    }
+}
+
+// Where the search for gaps is at in the inorder list of overlapping intervals.
+enum GapsState<'a, K> {
+    // No overlapping interval was visited yet.
+    Start,
+    // Keeps track of the maximum of a contiguous interval.
+    Contiguous(&'a Bound<K>),
+    Done,
+}
+
+/// An iterator through the subintervals of a range that are not covered by the inorder
+/// list of intervals overlapping it.
+pub(crate) struct Gaps<'a, K, Q: ?Sized, I> {
+    overlaps: I,
+    range: (Bound<&'a Q>, Bound<&'a Q>),
+    state: GapsState<'a, K>,
+    // Whether the subinterval following the last overlapping interval is yielded
+    // when the range is unbounded above.
+    trailing: bool,
+}
+
+impl<'a, K, Q, I> Gaps<'a, K, Q, I>
+where
+    K: Ord + Borrow<Q>,
+    Q: Ord + ?Sized,
+    I: Iterator<Item = &'a Range<K>>,
+{
+    pub(crate) fn new(overlaps: I, start: Bound<&'a Q>, end: Bound<&'a Q>) -> Self {
+        Gaps {
+            overlaps,
+            range: (start, end),
+            state: GapsState::Start,
+            trailing: false,
+        }
+    }
+
+    // Also yields the subinterval following the last overlapping interval when the range
+    // is unbounded above, which is not part of the difference (see `IntervalTree::gaps`).
+    fn with_trailing_gap(mut self) -> Self {
+        self.trailing = true;
+        self
+    }
+
+    // Visits the first overlapping interval, and returns the difference preceding it.
+    fn visit_first(&mut self, first: &'a Range<K>) -> Option<Range<&'a Q>> {
+        // If the max is unbounded, there can't be any difference going forward.
+        self.state = match first.1 {
+            Unbounded => GapsState::Done,
+            _ => GapsState::Contiguous(&first.1),
+        };
+
+        // If q.min < first.min, we have a difference to append.
+        match (self.range.0, &first.0) {
+            (Unbounded, Included(first_min)) => Some((Unbounded, Excluded(first_min.borrow()))),
+            (Unbounded, Excluded(first_min)) => Some((Unbounded, Included(first_min.borrow()))),
+            (Included(q_min), Included(first_min)) if q_min < first_min.borrow() => {
+                Some((Included(q_min), Excluded(first_min.borrow())))
+            }
+            (Excluded(q_min), Included(first_min)) if q_min < first_min.borrow() => {
+                Some((Excluded(q_min), Excluded(first_min.borrow())))
+            }
+            (Excluded(q_min), Excluded(first_min)) if q_min < first_min.borrow() => {
+                Some((Excluded(q_min), Included(first_min.borrow())))
+            }
+            (Included(q_min), Excluded(first_min)) if q_min <= first_min.borrow() => {
+                Some((Included(q_min), Included(first_min.borrow())))
+            }
+            _ => None,
+        }
+    }
+
+    // Visits an overlapping interval following the contiguous interval ending at `contiguous`,
+    // and returns the difference between them.
+    fn visit_next(&mut self, contiguous: &'a Bound<K>, overlap: &'a Range<K>) -> Option<Range<&'a Q>> {
+        let mut contiguous = contiguous;
+        let mut gap = None;
+
+        // If contiguous < overlap.min:
+        //   1. We have a difference between contiguous -> overlap.min to fill.
+        //     1.1: Note: the endpoints of the difference appended are the opposite,
+        //          that is if contiguous was Included, then the difference must
+        //          be Excluded, and vice versa.
+        //   2. We need to update contiguous to be the new contiguous max.
+        // Note: an Included+Excluded at the same point still is contiguous!
+        match (contiguous, &overlap.0) {
+            (Included(contiguous_max), Included(overlap_min))
+                if contiguous_max < overlap_min =>
+            {
+                gap = Some((
+                    Excluded(contiguous_max.borrow()),
+                    Excluded(overlap_min.borrow()),
+                ));
+                contiguous = &overlap.1;
+            }
+            (Included(contiguous_max), Excluded(overlap_min))
+                if contiguous_max < overlap_min =>
+            {
+                gap = Some((
+                    Excluded(contiguous_max.borrow()),
+                    Included(overlap_min.borrow()),
+                ));
+                contiguous = &overlap.1;
+            }
+            (Excluded(contiguous_max), Included(overlap_min))
+                if contiguous_max < overlap_min =>
+            {
+                gap = Some((
+                    Included(contiguous_max.borrow()),
+                    Excluded(overlap_min.borrow()),
+                ));
+                contiguous = &overlap.1;
+            }
+            (Excluded(contiguous_max), Excluded(overlap_min))
+                if contiguous_max <= overlap_min =>
+            {
+                gap = Some((
+                    Included(contiguous_max.borrow()),
+                    Included(overlap_min.borrow()),
+                ));
+                contiguous = &overlap.1;
+            }
+            _ => {}
+        }
+
+        // If contiguous.max < overlap.max, we set contiguous to the new max.
+        match (contiguous, &overlap.1) {
+            (_, Unbounded) => {
+                self.state = GapsState::Done;
+                return gap;
+            }
+            (Included(contiguous_max), Included(overlap_max))
+            | (Excluded(contiguous_max), Excluded(overlap_max))
+            | (Included(contiguous_max), Excluded(overlap_max))
+                if contiguous_max < overlap_max =>
+            {
+                contiguous = &overlap.1
+            }
+            (Excluded(contiguous_max), Included(overlap_max))
+                if contiguous_max <= overlap_max =>
+            {
+                contiguous = &overlap.1
+            }
+            _ => {}
+        };
+
+        self.state = GapsState::Contiguous(contiguous);
+        gap
+    }
+
+    // Returns the difference following the last contiguous interval, ending at `contiguous`.
+    fn visit_last(&mut self, contiguous: &'a Bound<K>) -> Option<Range<&'a Q>> {
+        self.state = GapsState::Done;
+
+        // If contiguous.max < q.max, we have a difference to append.
+        match (contiguous, self.range.1) {
+            (Included(contiguous_max), Included(q_max)) if contiguous_max.borrow() < q_max => {
+                Some((Excluded(contiguous_max.borrow()), Included(q_max)))
+            }
+            (Included(contiguous_max), Excluded(q_max)) if contiguous_max.borrow() < q_max => {
+                Some((Excluded(contiguous_max.borrow()), Excluded(q_max)))
+            }
+            (Excluded(contiguous_max), Excluded(q_max)) if contiguous_max.borrow() < q_max => {
+                Some((Included(contiguous_max.borrow()), Excluded(q_max)))
+            }
+            (Excluded(contiguous_max), Included(q_max)) if contiguous_max.borrow() <= q_max => {
+                Some((Included(contiguous_max.borrow()), Included(q_max)))
+            }
+            (Included(contiguous_max), Unbounded) if self.trailing => {
+                Some((Excluded(contiguous_max.borrow()), Unbounded))
+            }
+            (Excluded(contiguous_max), Unbounded) if self.trailing => {
+                Some((Included(contiguous_max.borrow()), Unbounded))
+            }
+            _ => None,
         }
+    }
+}
+
+impl<'a, K, Q, I> Iterator for Gaps<'a, K, Q, I>
+where
+    K: Ord + Borrow<Q>,
+    Q: Ord + ?Sized,
+    I: Iterator<Item = &'a Range<K>>,
+{
+    type Item = Range<&'a Q>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let gap = match self.state {
+                GapsState::Start => match self.overlaps.next() {
+                    Some(first) => self.visit_first(first),
+                    // If there is no overlap, then the difference is the query `q` itself.
+                    None => {
+                        self.state = GapsState::Done;
+                        return Some(self.range);
+                    }
+                },
+                GapsState::Contiguous(contiguous) => match self.overlaps.next() {
+                    Some(overlap) => self.visit_next(contiguous, overlap),
+                    None => self.visit_last(contiguous),
+                },
+                GapsState::Done => return None,
+            };
 
-        let visited = self.to_visit.pop();
-        self.curr = &visited.as_ref().unwrap().right;
-        Some(&visited.unwrap().key)
+            if gap.is_some() {
+                return gap;
+            }
+        }
     }
 }
 
 #[cfg(test)]
+#[allow(clippy::clone_on_copy, clippy::useless_vec)]
 mod tests {
     use super::*;
+    use crate::test_util::{bound, points};
     use serde_json::{Value, from_str, json, to_string};
+    use std::time::{Duration, Instant};
     
     #[test]
     fn serialize_deserialize_identity() {
@@ -994,9 +3098,103 @@ mod tests {
 	    },
 	    "size": 2,
 	});
-	let serialized_value = value.to_string();
-	let deserialized_tree = from_str(&serialized_value).unwrap();
-	assert_eq!(expected_tree, deserialized_tree);
+	let serialized_value = value.to_string();
+	let deserialized_tree = from_str(&serialized_value).unwrap();
+	assert_eq!(expected_tree, deserialized_tree);
+    }
+
+    #[test]
+    fn deserialize_invalid_interval_fails() {
+	let value = json!({
+	    "root": {
+		"key": [
+		    {"Included": 2},
+		    {"Included": 4},
+		],
+		"left": {
+		    "key": [
+			{"Excluded": 3},
+			{"Excluded": 3},
+		    ],
+		    "left": null,
+		    "right": null,
+		    "value": {"Excluded": 3},
+		},
+		"right": null,
+		"value": {"Included": 4},
+	    },
+	    "size": 2,
+	});
+	let err = from_str::<IntervalTree<i32>>(&value.to_string()).unwrap_err();
+	assert!(err.to_string().contains("the interval is empty"));
+    }
+
+    #[test]
+    fn deserialize_restores_invariants() {
+	// The size is recomputed, and the index of end-points is rebuilt.
+	let tree = IntervalTree::from([0..5, 3..9, 4..6]);
+	let serialized_tree = to_string(&tree).unwrap().replace(r#""size":3"#, r#""size":42"#);
+	let deserialized_tree: IntervalTree<i32> = from_str(&serialized_tree).unwrap();
+	assert_eq!(tree, deserialized_tree);
+	assert_eq!(deserialized_tree.count_overlapping(&(5..7)), 2);
+
+	let node = |start, end, left, right| {
+	    json!({
+		"key": [{"Included": start}, {"Excluded": end}],
+		"left": left,
+		"right": right,
+	    })
+	};
+
+	// Intervals must be sorted.
+	let value = json!({
+	    "root": node(0, 5, node(3, 9, Value::Null, Value::Null), Value::Null),
+	    "size": 2,
+	});
+	let err = from_str::<IntervalTree<i32>>(&value.to_string()).unwrap_err();
+	assert!(err.to_string().contains("the intervals are not sorted"));
+
+	// The tree must be balanced.
+	let left = node(0, 5, Value::Null, Value::Null);
+	let value = json!({
+	    "root": node(4, 6, node(3, 9, left, Value::Null), Value::Null),
+	    "size": 3,
+	});
+	let err = from_str::<IntervalTree<i32>>(&value.to_string()).unwrap_err();
+	assert!(err.to_string().contains("the tree is unbalanced"));
+    }
+
+    #[test]
+    fn validate_works_as_expected() {
+        let cases = [
+            ((Included(1), Included(2)), Ok(())),
+            ((Excluded(1), Excluded(2)), Ok(())),
+            ((Included(2), Included(2)), Ok(())),
+            ((Included(2), Excluded(2)), Err(IntervalError::Empty)),
+            ((Excluded(2), Included(2)), Err(IntervalError::Empty)),
+            ((Excluded(2), Excluded(2)), Err(IntervalError::Empty)),
+            ((Included(3), Included(2)), Err(IntervalError::Inverted)),
+            ((Excluded(3), Excluded(2)), Err(IntervalError::Inverted)),
+            ((Unbounded, Excluded(2)), Ok(())),
+            ((Excluded(2), Unbounded), Ok(())),
+            ((Unbounded, Unbounded), Ok(())),
+        ];
+
+        for (range, expected) in cases {
+            assert_eq!(IntervalTree::validate(&range), expected, "{:?}", range);
+
+            let mut tree = IntervalTree::default();
+            assert_eq!(tree.try_insert(range).map(|_| ()), expected);
+            assert_eq!(tree.len(), expected.map_or(0, |_| 1));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot insert an invalid interval: the start bound of the interval is greater than its end bound")]
+    fn from_iterator_with_invalid_interval_panics() {
+        let _ = [(Included(0), Excluded(5)), (Included(9), Excluded(5))]
+            .into_iter()
+            .collect::<IntervalTree<_>>();
     }
     
     #[test]
@@ -1052,16 +3250,18 @@ mod tests {
             left_key.1
         );
 
+        // The insertion unbalances the tree, which is fixed with a left-right rotation.
         tree.insert(left_right_key.clone());
-        assert!(tree
-            .root
-            .as_ref()
-            .unwrap()
-            .left
-            .as_ref()
-            .unwrap()
-            .right
-            .is_some());
+        assert_eq!(tree.root.as_ref().unwrap().key, left_right_key);
+        assert_eq!(
+            tree.root.as_ref().unwrap().left.as_ref().unwrap().key,
+            left_key
+        );
+        assert_eq!(
+            tree.root.as_ref().unwrap().right.as_ref().unwrap().key,
+            root_key
+        );
+        assert_eq!(tree.height(), 2);
     }
 
     #[test]
@@ -1084,33 +3284,17 @@ mod tests {
             left_key.1
         );
 
+        // The insertion unbalances the tree, which is fixed with a right rotation.
         tree.insert(left_left_key.clone());
+        assert_eq!(tree.root.as_ref().unwrap().key, left_key);
         assert_eq!(tree.root.as_ref().unwrap().value, left_left_key.1);
         assert_eq!(
             tree.root.as_ref().unwrap().left.as_ref().unwrap().value,
             left_left_key.1
         );
-        assert!(tree
-            .root
-            .as_ref()
-            .unwrap()
-            .left
-            .as_ref()
-            .unwrap()
-            .left
-            .is_some());
         assert_eq!(
-            tree.root
-                .as_ref()
-                .unwrap()
-                .left
-                .as_ref()
-                .unwrap()
-                .left
-                .as_ref()
-                .unwrap()
-                .value,
-            left_left_key.1
+            tree.root.as_ref().unwrap().right.as_ref().unwrap().value,
+            root_key.1
         );
 
         tree.insert(right_key.clone());
@@ -1199,6 +3383,27 @@ mod tests {
         );
     }
 
+    #[test]
+    fn overlaps_iterator_works_as_expected() {
+        let tree = random_tree(200, 50, 13);
+
+        // The lazy iterator yields the same intervals as a naive scan, in order.
+        for query in queries(50, 8) {
+            let expected = brute_force(&tree, |range| {
+                !IntervalTree::is_endbound_before(&range.1, &query) && !IntervalTree::is_startbound_after(&range.0, &query)
+            });
+            assert_eq!(tree.overlaps(&query).collect::<Vec<_>>(), expected);
+        }
+
+        // Stopping early only visits what was needed.
+        let tree = (0..1000).map(|i| i..i + 2).collect::<IntervalTree<_>>();
+        assert_eq!(
+            tree.overlaps(&(500..)).take(2).collect::<Vec<_>>(),
+            vec![&(Included(499), Excluded(501)), &(Included(500), Excluded(502))]
+        );
+        assert_eq!(tree.overlaps(&(..0)).next(), None);
+    }
+
     #[test]
     fn difference_and_overlaps_with_tuple_works_as_expected() {
         let mut tree = IntervalTree::default();
@@ -1302,6 +3507,102 @@ mod tests {
         );
     }
 
+    #[test]
+    fn gaps_works_as_expected() {
+        let mut tree = IntervalTree::default();
+
+        let key1 = (Included(10), Excluded(20));
+        let key2 = (Excluded(30), Excluded(40));
+        let key3 = (Included(15), Included(25));
+        let key4 = (Excluded(50), Unbounded);
+
+        tree.insert(key1);
+        tree.insert(key2);
+        tree.insert(key3);
+        tree.insert(key4);
+
+        let mut gaps = tree.gaps(&(0..=100));
+        assert_eq!(gaps.next(), Some((Included(&0), Excluded(&10))));
+        assert_eq!(gaps.next(), Some((Excluded(&25), Included(&30))));
+        assert_eq!(gaps.next(), Some((Included(&40), Included(&50))));
+        assert_eq!(gaps.next(), None);
+        assert_eq!(gaps.next(), None);
+
+        // The gaps are exactly the points of the query not covered by the tree.
+        for query in [0..=100, 10..=25, 26..=29, 40..=40, 51..=60] {
+            let gaps = tree.gaps(&query).collect::<Vec<_>>();
+            assert_eq!(gaps, tree.get_interval_difference(&query));
+
+            for p in query.clone() {
+                let in_gap = gaps.iter().any(|gap| gap.contains(&p));
+                assert_eq!(in_gap, !tree.contains_point(&p));
+            }
+        }
+    }
+
+    #[test]
+    fn difference_unbounded_above_excludes_trailing_gap() {
+        let tree = IntervalTree::from([(Included(10), Excluded(20)), (Excluded(30), Included(40))]);
+
+        // The points following the last interval overlapping the query are not part of the
+        // difference, but they still count towards the uncovered measure.
+        assert_eq!(tree.get_interval_difference(&(15..)), vec![(Included(&20), Included(&30))]);
+        assert!(!tree.contains_interval(&(15..)));
+        assert_eq!(tree.uncovered_measure(&(15..)), None);
+        assert_eq!(tree.uncovered_measure(&(15..=40)), Some(10u32));
+    }
+
+    #[test]
+    fn set_operations_work_as_expected() {
+        use crate::set_ops::Cut;
+
+        let tree = |seed: i32| {
+            (0..12)
+                .map(|i| {
+                    let start = (i * seed) % 25;
+                    let end = start + (i * 7 + seed) % 4;
+                    (bound(start, i + seed), bound(end, i * seed + 1))
+                })
+                .filter(|range| IntervalTree::validate(range).is_ok())
+                .collect::<IntervalTree<_>>()
+        };
+        let contains = |tree: &IntervalTree<i32>, p: i32| tree.iter().any(|range| range.contains(&p));
+
+        let trees = [tree(3), tree(5), tree(11), IntervalTree::from([..]), IntervalTree::default()];
+        for a in &trees {
+            for b in &trees {
+                let results = [
+                    a.union(b).collect::<Vec<_>>(),
+                    a.intersection(b).collect(),
+                    a.difference(b).collect(),
+                    a.symmetric_difference(b).collect(),
+                ];
+                let operators = [a | b, a & b, a - b, a ^ b];
+                let ops: [fn(bool, bool) -> bool; 4] =
+                    [|a, b| a || b, |a, b| a && b, |a, b| a && !b, |a, b| a != b];
+
+                for ((ranges, tree), op) in results.iter().zip(operators).zip(ops) {
+                    // Normalized intervals are sorted, and neither overlapping nor adjacent.
+                    for pair in ranges.windows(2) {
+                        assert!(Cut::from_end(pair[0].1) < Cut::from_start(pair[1].0));
+                    }
+                    assert_eq!(
+                        tree,
+                        ranges
+                            .iter()
+                            .map(|&(start, end)| (start.cloned(), end.cloned()))
+                            .collect::<IntervalTree<_>>()
+                    );
+
+                    for p in -4..60 {
+                        let in_ranges = ranges.iter().any(|range| range.contains(&p));
+                        assert_eq!(in_ranges, op(contains(a, p), contains(b, p)), "{}", p);
+                    }
+                }
+            }
+        }
+    }
+
     #[test]
     fn get_interval_difference_str_works_as_expected() {
         let mut tree: IntervalTree<&str> = IntervalTree::default();
@@ -1349,6 +3650,47 @@ mod tests {
         assert!(tree.contains_point(&100));
     }
 
+    #[test]
+    fn point_overlaps_works_as_expected() {
+        let mut tree = IntervalTree::default();
+
+        let key1 = (Included(10), Excluded(20));
+        let key2 = (Unbounded, Included(10));
+        let key3 = (Excluded(10), Unbounded);
+        let key4 = (Included(15), Included(15));
+        let key5 = (Excluded(20), Excluded(30));
+
+        tree.insert(key1);
+        tree.insert(key2);
+        tree.insert(key3);
+        tree.insert(key4);
+        tree.insert(key5);
+
+        assert_eq!(
+            tree.get_point_overlaps(&10).collect::<Vec<_>>(),
+            vec![&key2, &key1]
+        );
+        assert_eq!(
+            tree.get_point_overlaps(&15).collect::<Vec<_>>(),
+            vec![&key1, &key3, &key4]
+        );
+        assert_eq!(tree.get_point_overlaps(&20).collect::<Vec<_>>(), vec![&key3]);
+        assert_eq!(
+            tree.get_point_overlaps(&25).collect::<Vec<_>>(),
+            vec![&key3, &key5]
+        );
+        assert_eq!(tree.get_point_overlaps(&-100).collect::<Vec<_>>(), vec![&key2]);
+
+        // Every interval containing the point is returned, in order.
+        for p in 0..40 {
+            let expected = tree
+                .iter()
+                .filter(|range| range.contains(&p))
+                .collect::<Vec<_>>();
+            assert_eq!(tree.get_point_overlaps(&p).collect::<Vec<_>>(), expected);
+        }
+    }
+
     #[test]
     fn contains_string_point_works_as_expected() {
         let mut tree = IntervalTree::default();
@@ -1399,6 +3741,421 @@ mod tests {
         assert!(tree.contains_interval(&(Included(35), Included(37))));
     }
 
+    #[test]
+    fn contains_agrees_with_difference() {
+        let contiguous = IntervalTree::from([
+            (Included(0), Included(10)),
+            (Excluded(10), Excluded(20)),
+            (Included(20), Excluded(30)),
+            (Included(5), Included(8)),
+        ]);
+
+        for tree in [random_tree(40, 50, 13), contiguous, IntervalTree::default()] {
+            // Empty ranges have no point to cover.
+            for query in queries(50, 10).filter(|query| IntervalTree::validate(query).is_ok()) {
+                // The difference does not include the points following the last interval
+                // overlapping a query unbounded above, which are covered if it is unbounded too.
+                let covers_tail = query.1 != Unbounded || tree.overlaps(&query).any(|range| range.1 == Unbounded);
+                assert_eq!(
+                    tree.contains_interval(&query),
+                    tree.get_interval_difference(&query).is_empty() && covers_tail,
+                    "{:?}",
+                    query
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn depth_profile_agrees_with_count() {
+        let tree = random_tree(100, 50, 13);
+
+        for query in queries(50, 7).filter(|query| IntervalTree::validate(query).is_ok()) {
+            let profile = tree.depth_profile(&query).collect::<Vec<_>>();
+
+            // Segments are contiguous, cover `query`, and have distinct depths.
+            assert_eq!(Cut::from_start(profile[0].0 .0), Cut::from_start(query.0.as_ref()));
+            assert_eq!(Cut::from_end(profile[profile.len() - 1].0 .1), Cut::from_end(query.1.as_ref()));
+            for pair in profile.windows(2) {
+                assert_eq!(Cut::from_end(pair[0].0 .1), Cut::from_start(pair[1].0 .0));
+                assert_ne!(pair[0].1, pair[1].1);
+            }
+
+            // Points outside of `query` are in no segment.
+            for p in points(50, 13) {
+                let depth = profile.iter().find(|(segment, _)| segment.contains(&&p)).map(|(_, depth)| *depth);
+                assert_eq!(depth, query.contains(&p).then(|| tree.count_containing(&p)));
+            }
+
+            let (max_depth, segments) = tree.max_depth(&query);
+            assert_eq!(max_depth, profile.iter().map(|(_, depth)| *depth).max().unwrap());
+            assert_eq!(
+                segments,
+                profile
+                    .into_iter()
+                    .filter(|(_, depth)| *depth == max_depth)
+                    .map(|(segment, _)| segment)
+                    .collect::<Vec<_>>()
+            );
+        }
+
+        assert_eq!(tree.depth_profile(&(Excluded(4), Excluded(4))).count(), 0);
+        assert_eq!(tree.max_depth(&(Included(10), Included(4))), (0, vec![]));
+    }
+
+    #[test]
+    fn segment_agrees_with_point_overlaps() {
+        let tree = random_tree(60, 40, 11);
+
+        for query in queries(40, 5).filter(|query| IntervalTree::validate(query).is_ok()) {
+            let segments = tree.segment(&query);
+
+            // Segments are contiguous, cover `query`, and have distinct covering intervals.
+            assert_eq!(Cut::from_start(segments[0].0 .0), Cut::from_start(query.0.as_ref()));
+            assert_eq!(Cut::from_end(segments[segments.len() - 1].0 .1), Cut::from_end(query.1.as_ref()));
+            for pair in segments.windows(2) {
+                assert_eq!(Cut::from_end(pair[0].0 .1), Cut::from_start(pair[1].0 .0));
+                assert_ne!(pair[0].1, pair[1].1);
+            }
+
+            // Points outside of `query` are in no segment.
+            for p in points(40, 11) {
+                let covering = segments.iter().find(|(segment, _)| segment.contains(&&p)).map(|(_, covering)| covering.clone());
+                assert_eq!(covering, query.contains(&p).then(|| tree.get_point_overlaps(&p).collect()));
+            }
+        }
+
+        assert!(tree.segment(&(Excluded(4), Excluded(4))).is_empty());
+        assert_eq!(
+            IntervalTree::<i32>::default().segment(&(..)),
+            vec![((Unbounded, Unbounded), vec![])]
+        );
+    }
+
+    #[test]
+    fn events_sweep_the_overlaps_in_order() {
+        let tree = random_tree(60, 40, 11);
+
+        for query in queries(40, 5).filter(|query| IntervalTree::validate(query).is_ok()) {
+            let events = tree.events(&query).collect::<Vec<_>>();
+
+            // Every overlap is entered then exited, at the cuts of its bounds,
+            // and exits come first at the same cut.
+            let cuts = events
+                .iter()
+                .map(|event| match event {
+                    Event::Enter(key) => (Cut::from_start(key.0.as_ref()), 1),
+                    Event::Exit(key) => (Cut::from_end(key.1.as_ref()), 0),
+                })
+                .collect::<Vec<_>>();
+            assert!(cuts.windows(2).all(|pair| pair[0] <= pair[1]));
+
+            let mut active = vec![];
+            let mut entered = vec![];
+            for (i, (event, (cut, _))) in events.iter().zip(&cuts).enumerate() {
+                // Before the events at a cut, the active intervals contain the odd point right before it.
+                if let Cut::Below(x) = *cut {
+                    let p = x - 1;
+                    if (i == 0 || cuts[i - 1].0 != *cut) && query.contains(&p) {
+                        assert_eq!(active.len(), tree.count_containing(&p));
+                    }
+                }
+                match event {
+                    Event::Enter(key) => {
+                        active.push(*key);
+                        entered.push(*key);
+                    }
+                    Event::Exit(key) => {
+                        let position = active.iter().position(|active| active == key).unwrap();
+                        active.remove(position);
+                    }
+                }
+            }
+            assert!(active.is_empty());
+            assert_eq!(entered, tree.get_interval_overlaps(&query));
+        }
+
+        let tree = IntervalTree::from([(Included(0), Excluded(5)), (Included(5), Included(5)), (Excluded(5), Unbounded)]);
+        assert_eq!(
+            tree.events(&(..)).collect::<Vec<_>>(),
+            vec![
+                Event::Enter(&(Included(0), Excluded(5))),
+                Event::Exit(&(Included(0), Excluded(5))),
+                Event::Enter(&(Included(5), Included(5))),
+                Event::Exit(&(Included(5), Included(5))),
+                Event::Enter(&(Excluded(5), Unbounded)),
+                Event::Exit(&(Excluded(5), Unbounded)),
+            ]
+        );
+    }
+
+    #[test]
+    fn measures_agree_with_points() {
+        let tree = random_tree(60, 40, 11);
+
+        for query in queries(40, 5) {
+            let covered = tree.covered_measure(&query);
+            let uncovered = tree.uncovered_measure(&query);
+
+            // Every odd point stands for the length 2 between the keys around it.
+            if let (Included(x) | Excluded(x), Included(y) | Excluded(y)) = query {
+                let points = (x..y).filter(|p| p % 2 != 0).filter(|p| query.contains(p));
+                let (covered_points, uncovered_points) = points.partition::<Vec<_>, _>(|p| tree.count_containing(p) > 0);
+                assert_eq!(covered, Some(2 * covered_points.len() as u32));
+                assert_eq!(uncovered, Some(2 * uncovered_points.len() as u32));
+                if x < y {
+                    let ratio = covered_points.len() as f64 / (covered_points.len() + uncovered_points.len()) as f64;
+                    assert_eq!(tree.coverage_ratio(&query), Some(ratio));
+                }
+            } else {
+                assert!(covered.is_none() || uncovered.is_none());
+                assert_eq!(tree.coverage_ratio(&query), None);
+            }
+        }
+
+        let now = Instant::now();
+        let minute = Duration::from_secs(60);
+        let tree = IntervalTree::from([now + minute..now + minute * 3, now + minute * 2..now + minute * 4]);
+        assert_eq!(tree.covered_measure(&(now..now + minute * 10)), Some(minute * 3));
+        assert_eq!(tree.uncovered_measure(&(now..now + minute * 10)), Some(minute * 7));
+        assert_eq!(tree.coverage_ratio(&(now + minute * 3..now + minute * 5)), Some(0.5));
+    }
+
+    #[test]
+    fn containment_agrees_with_filtered_overlaps() {
+        let tree = random_tree(200, 60, 17);
+
+        for query in queries(60, 10) {
+            let query_start = Cut::from_start(query.0);
+            let query_end = Cut::from_end(query.1);
+            let cuts = |range: &Range<i32>| (Cut::from_start(range.0), Cut::from_end(range.1));
+            let non_empty = query_start < query_end;
+
+            assert_eq!(
+                tree.get_contained_in(&query).collect::<Vec<_>>(),
+                brute_force(&tree, |range| non_empty && query_start <= cuts(range).0 && cuts(range).1 <= query_end)
+            );
+            assert_eq!(
+                tree.get_containing(&query).collect::<Vec<_>>(),
+                brute_force(&tree, |range| non_empty && cuts(range).0 <= query_start && query_end <= cuts(range).1)
+            );
+        }
+    }
+
+    #[test]
+    fn query_relation_agrees_with_classify() {
+        let relations = [
+            AllenRelation::Before,
+            AllenRelation::Meets,
+            AllenRelation::Overlaps,
+            AllenRelation::Starts,
+            AllenRelation::During,
+            AllenRelation::Finishes,
+            AllenRelation::Equals,
+            AllenRelation::FinishedBy,
+            AllenRelation::Contains,
+            AllenRelation::StartedBy,
+            AllenRelation::OverlappedBy,
+            AllenRelation::MetBy,
+            AllenRelation::After,
+        ];
+        let tree = random_tree(80, 40, 13);
+
+        for query in queries(40, 8) {
+            if IntervalTree::validate(&query).is_err() {
+                for relation in relations {
+                    assert_eq!(tree.query_relation(&query, relation).next(), None);
+                }
+                continue;
+            }
+
+            for relation in relations {
+                assert_eq!(
+                    tree.query_relation(&query, relation).collect::<Vec<_>>(),
+                    brute_force(&tree, |range| AllenRelation::classify(range, &query) == relation)
+                );
+            }
+            for range in tree.iter() {
+                let relation = AllenRelation::classify(range, &query);
+                assert_eq!(AllenRelation::classify(&query, range), relation.inverse());
+                // Only the relations where intervals share points hold for overlapping intervals.
+                let disjoint = [AllenRelation::Before, AllenRelation::Meets, AllenRelation::MetBy, AllenRelation::After];
+                assert_eq!(!disjoint.contains(&relation), tree.overlaps(&query).any(|overlap| overlap == range));
+            }
+        }
+
+        // Contiguity follows the exact semantics of the bounds.
+        let cases = [
+            ((Included(0), Excluded(5)), (Included(5), Included(9)), AllenRelation::Meets),
+            ((Included(0), Included(5)), (Excluded(5), Included(9)), AllenRelation::Meets),
+            ((Included(0), Excluded(5)), (Excluded(5), Included(9)), AllenRelation::Before),
+            ((Included(0), Included(5)), (Included(5), Included(9)), AllenRelation::Overlaps),
+            ((Unbounded, Included(5)), (Unbounded, Included(9)), AllenRelation::Starts),
+            ((Included(0), Unbounded), (Excluded(0), Unbounded), AllenRelation::FinishedBy),
+            ((Included(5), Included(5)), (Included(5), Included(9)), AllenRelation::Starts),
+            ((Included(5), Included(5)), (Included(0), Excluded(5)), AllenRelation::MetBy),
+        ];
+        for (a, b, relation) in cases {
+            assert_eq!(AllenRelation::classify(&a, &b), relation);
+            assert_eq!(AllenRelation::classify(&b, &a), relation.inverse());
+        }
+    }
+
+    #[test]
+    fn innermost_and_outermost_are_nesting_extremes() {
+        let tree = random_tree(80, 40, 13);
+        let nested = |inner: &Range<i32>, outer: &Range<i32>| {
+            inner != outer
+                && Cut::from_start(outer.0.as_ref()) <= Cut::from_start(inner.0.as_ref())
+                && Cut::from_end(inner.1.as_ref()) <= Cut::from_end(outer.1.as_ref())
+        };
+
+        for p in points(40, 13) {
+            let containing = tree.get_point_overlaps(&p).collect::<Vec<_>>();
+
+            // The first of the intervals containing no other one (resp. contained in no other one).
+            let innermost = containing
+                .iter()
+                .find(|range| !containing.iter().any(|other| nested(other, range)))
+                .copied();
+            let outermost = containing
+                .iter()
+                .find(|range| !containing.iter().any(|other| nested(range, other)))
+                .copied();
+
+            assert_eq!(tree.innermost_containing(&p), innermost);
+            assert_eq!(tree.outermost_containing(&p), outermost);
+        }
+    }
+
+    #[test]
+    fn neighbours_agree_with_brute_force() {
+        let tree = random_tree(80, 40, 5);
+
+        for p in points(40, 5) {
+            let predecessor = tree
+                .iter()
+                .filter(|range| Cut::from_end(range.1.as_ref()) <= Cut::Below(&p))
+                .max_by(|r1, r2| {
+                    Cut::from_end(r1.1.as_ref())
+                        .cmp(&Cut::from_end(r2.1.as_ref()))
+                        .then_with(|| IntervalTree::cmp(r2, r1))
+                });
+            let successor = tree.iter().find(|range| Cut::from_start(range.0.as_ref()) >= Cut::Above(&p));
+            assert_eq!(tree.predecessor(&p), predecessor);
+            assert_eq!(tree.successor(&p), successor);
+
+            let distance = |range: &Range<i32>| match range {
+                (Included(start) | Excluded(start), _) if p < *start => start - p,
+                (_, Included(end) | Excluded(end)) if *end <= p => p - end,
+                _ => 0,
+            };
+            let nearest = tree.iter().filter(|range| range.contains(&p)).chain(predecessor).chain(successor).min_by_key(|range| distance(range));
+            assert_eq!(tree.nearest(&p), nearest);
+        }
+
+        let empty = IntervalTree::<i32>::default();
+        assert_eq!(empty.predecessor(&0), None);
+        assert_eq!(empty.successor(&0), None);
+        assert_eq!(empty.nearest(&0), None);
+    }
+
+    #[test]
+    fn neighbours_skip_nested_intervals() {
+        // Many nested intervals contain the point, and end after its predecessor.
+        let mut tree = (1..1000).map(|i| -i..50 + i).collect::<IntervalTree<_>>();
+        tree.insert((Excluded(-5), Excluded(-3)));
+        tree.insert(-4..-3);
+        tree.insert(-5..-3);
+        tree.insert(60..70);
+
+        assert_eq!(tree.predecessor(&10), Some(&(Included(-5), Excluded(-3))));
+        assert_eq!(tree.successor(&10), Some(&(Included(60), Excluded(70))));
+        assert_eq!(tree.nearest(&10), Some(&(Included(-999), Excluded(1049))));
+
+        // The intervals ending together are kept sorted by start-point through removals.
+        tree.remove(&(-5..-3));
+        assert_eq!(tree.predecessor(&10), Some(&(Excluded(-5), Excluded(-3))));
+        tree.remove(&(Excluded(-5), Excluded(-3)));
+        assert_eq!(tree.predecessor(&10), Some(&(Included(-4), Excluded(-3))));
+        tree.remove(&(-4..-3));
+        assert_eq!(tree.predecessor(&10), None);
+        assert_eq!(tree.predecessor(&1049), Some(&(Included(-999), Excluded(1049))));
+    }
+
+    #[test]
+    fn many_intervals_sharing_an_end() {
+        // The index of end-points is updated in `O(log(n))` per insertion and removal,
+        // even when all the intervals end together.
+        let n = 100_000;
+        let tree = (0..n).rev().map(|i| i..).collect::<IntervalTree<_>>();
+        assert_eq!(tree.len(), n as usize);
+        assert_eq!(tree.count_containing(&(n / 2)), n as usize / 2 + 1);
+        assert_eq!(tree.predecessor(&n), None);
+
+        let mut tree = (0..n).rev().map(|i| i..n).collect::<IntervalTree<_>>();
+        assert_eq!(tree.predecessor(&n), Some(&(Included(0), Excluded(n))));
+        for i in 0..n / 2 {
+            assert_eq!(tree.remove(&(i..n)), Some((Included(i), Excluded(n))));
+        }
+        assert_eq!(tree.predecessor(&n), Some(&(Included(n / 2), Excluded(n))));
+        assert_eq!(tree.count_overlapping(&(n..)), 0);
+        assert_eq!(tree.count_overlapping(&(n - 1..)), n as usize / 2);
+    }
+
+    #[test]
+    fn endpoint_queries_agree_with_filtered_intervals() {
+        let tree = random_tree(200, 60, 17);
+
+        for query in queries(60, 10) {
+            let query_start = Cut::from_start(query.0);
+            let query_end = Cut::from_end(query.1);
+
+            // The first (resp. last) points of an interval are in `query`.
+            assert_eq!(
+                tree.starting_in(&query).collect::<Vec<_>>(),
+                brute_force(&tree, |range| (query_start..query_end).contains(&Cut::from_start(range.0)))
+            );
+            assert_eq!(
+                tree.ending_in(&query).collect::<Vec<_>>(),
+                brute_force(&tree, |range| query_start < Cut::from_end(range.1) && Cut::from_end(range.1) <= query_end)
+            );
+        }
+
+        let tree = IntervalTree::from([(Included(0), Excluded(5)), (Excluded(5), Included(9))]);
+        assert_eq!(tree.starting_in(&(Included(0), Included(5))).count(), 1);
+        assert_eq!(tree.starting_in(&(Excluded(5), Excluded(7))).count(), 1);
+        assert_eq!(tree.ending_in(&(Included(5), Included(9))).count(), 1);
+        assert_eq!(tree.ending_in(&(Excluded(3), Excluded(5))).count(), 1);
+    }
+
+    #[test]
+    fn count_agrees_with_overlaps() {
+        let mut tree = random_tree(200, 50, 13);
+
+        while !tree.is_empty() {
+            for p in points(50, 13) {
+                assert_eq!(tree.count_containing(&p), tree.get_point_overlaps(&p).count());
+            }
+            for query in queries(50, 8) {
+                // Empty ranges overlap with no interval.
+                let expected = match IntervalTree::validate(&query) {
+                    Ok(()) => tree.overlaps(&query).count(),
+                    Err(_) => 0,
+                };
+                assert_eq!(tree.count_overlapping(&query), expected, "{:?}", query);
+            }
+
+            // The index of end-points is kept up-to-date through removals.
+            let removed = tree.iter().step_by(3).cloned().collect::<Vec<_>>();
+            for range in &removed {
+                tree.remove(range);
+            }
+        }
+
+        assert_eq!(tree.count_overlapping(&(..)), 0);
+    }
+
     #[test]
     fn contains_str_works_as_expected() {
         let mut tree: IntervalTree<&str> = IntervalTree::default();
@@ -1504,15 +4261,29 @@ mod tests {
         let key4_deleted6 = (Excluded(15), Included(23));
         let key5_deleted6 = (Included(0), Included(3));
 
-        tree_deleted_key6.insert(key1_deleted6.clone());
         tree_deleted_key6.insert(key2_deleted6.clone());
         tree_deleted_key6.insert(key3_deleted6.clone());
         tree_deleted_key6.insert(key4_deleted6.clone());
+        tree_deleted_key6.insert(key1_deleted6.clone());
         tree_deleted_key6.insert(key5_deleted6.clone());
 
+        let mut tree_deleted_key1 = IntervalTree::default();
+
+        let key2_deleted1 = (Included(8), Excluded(9));
+        let key3_deleted1 = (Included(5), Excluded(8));
+        let key4_deleted1 = (Excluded(15), Included(23));
+        let key5_deleted1 = (Included(0), Included(3));
+        let key6_deleted1 = (Included(13), Excluded(26));
+
+        tree_deleted_key1.insert(key2_deleted1);
+        tree_deleted_key1.insert(key3_deleted1);
+        tree_deleted_key1.insert(key4_deleted1);
+        tree_deleted_key1.insert(key5_deleted1);
+        tree_deleted_key1.insert(key6_deleted1);
+
         use std::collections::HashSet;
         let mut all_deleted = HashSet::new();
-        let num_of_leaves = 2; // Key5 & Key6
+        let num_of_leaves = 3; // Key1, Key5 & Key6 (the tree is rebalanced after insertions)
 
         // This loop makes sure that the deletion is random.
         // We delete and reinsert leaves until we have deleted
@@ -1529,6 +4300,8 @@ mod tests {
                 assert_eq!(tree, tree_deleted_key5);
             } else if deleted == key6 {
                 assert_eq!(tree, tree_deleted_key6);
+            } else if deleted == key1 {
+                assert_eq!(tree, tree_deleted_key1);
             } else {
                 unreachable!();
             }
@@ -1541,6 +4314,221 @@ mod tests {
         }
     }
 
+    #[test]
+    fn remove_empty_tree_works_as_expected() {
+        let mut tree: IntervalTree<i32> = IntervalTree::default();
+
+        assert_eq!(tree.remove(&(0..1)), None);
+        assert_eq!(tree.len(), 0);
+    }
+
+    #[test]
+    fn remove_missing_key_works_as_expected() {
+        let mut tree = IntervalTree::default();
+
+        let key1 = (Included(10), Excluded(20));
+        let key2 = (Excluded(30), Excluded(40));
+
+        tree.insert(key1);
+        tree.insert(key2);
+
+        let expected_tree = tree.clone();
+
+        assert_eq!(tree.remove(&(Included(10), Included(20))), None);
+        assert_eq!(tree.remove(&(Included(30), Excluded(40))), None);
+        assert_eq!(tree.len(), 2);
+        assert_eq!(tree, expected_tree);
+    }
+
+    #[test]
+    fn remove_works_as_expected() {
+        let key1 = (Included(16), Unbounded);
+        let key2 = (Included(8), Excluded(9));
+        let key3 = (Included(5), Excluded(8));
+        let key4 = (Excluded(15), Included(23));
+        let key5 = (Included(0), Included(3));
+        let key6 = (Included(13), Excluded(26));
+        let keys = [key1, key2, key3, key4, key5, key6];
+
+        // Once balanced, the tree looks like this:
+        //            key2
+        //          /      \
+        //       key3      key4
+        //       /        /    \
+        //    key5     key6    key1
+        //
+        // Removing the root (two children), an internal node with one child,
+        // an internal node with two children and a leaf, respectively.
+        // When removing a node with two children, its inorder successor
+        // takes its place, hence the insertion orders of the expected trees.
+        let cases = [
+            (key2, vec![key6, key3, key4, key5, key1]),
+            (key3, vec![key2, key5, key4, key6, key1]),
+            (key4, vec![key2, key3, key1, key5, key6]),
+            (key5, vec![key2, key3, key4, key6, key1]),
+        ];
+
+        for (removed, expected_keys) in cases {
+            let mut tree: IntervalTree<_> = keys.into_iter().collect();
+            let expected_tree: IntervalTree<_> = expected_keys.into_iter().collect();
+
+            assert_eq!(tree.remove(&removed), Some(removed));
+            assert_eq!(tree.len(), keys.len() - 1);
+            assert_eq!(tree, expected_tree);
+            assert_eq!(tree.remove(&removed), None);
+        }
+
+        // Removing every key, one at a time, keeps the tree balanced
+        // and the max end-points up-to-date.
+        let mut tree: IntervalTree<_> = keys.into_iter().collect();
+        for (idx, removed) in [key5, key3, key1, key2, key6, key4].iter().enumerate() {
+            assert_eq!(tree.remove(removed), Some(*removed));
+            assert_eq!(tree.len(), keys.len() - idx - 1);
+            assert_valid(&tree);
+        }
+
+        assert!(tree.is_empty());
+        assert_eq!(tree.root, None);
+    }
+
+    #[test]
+    fn insert_and_remove_keep_tree_balanced() {
+        let mut tree = IntervalTree::default();
+
+        // Sorted insertions are the worst case for an unbalanced tree.
+        for i in 0..1024 {
+            tree.insert(i..i + 10);
+            assert_valid(&tree);
+        }
+        assert_eq!(tree.len(), 1024);
+        assert_eq!(tree.height(), 11);
+
+        for i in (0..1024).step_by(2) {
+            assert_eq!(tree.remove(&(i..i + 10)), Some((Included(i), Excluded(i + 10))));
+            assert_valid(&tree);
+        }
+        assert_eq!(tree.len(), 512);
+        assert!(tree.height() <= 10);
+
+        let overlaps = tree.get_interval_overlaps(&(500..=510));
+        assert_eq!(overlaps.len(), 10);
+        assert_eq!(overlaps[0], &(Included(491), Excluded(501)));
+    }
+
+    // Asserts that every node of the tree is balanced, and that every node
+    // stores its height, its number of intervals and the max end-point of its subtree.
+    fn assert_valid<K: Ord + Clone + fmt::Debug>(tree: &IntervalTree<K>) {
+        fn assert_valid_rec<K: Ord + Clone + fmt::Debug>(node: &Option<Box<Node<K, (), Size>>>) {
+            let node = match node {
+                None => return,
+                Some(node) => node,
+            };
+            assert_valid_rec(&node.left);
+            assert_valid_rec(&node.right);
+
+            let left_height = Node::height(&node.left);
+            let right_height = Node::height(&node.right);
+            assert!(left_height.abs_diff(right_height) <= 1);
+            assert_eq!(node.height, 1 + left_height.max(right_height));
+            assert_eq!(node.aggregate.0, Size::of(&node.left) + 1 + Size::of(&node.right));
+
+            let mut max = &node.key.1;
+            for child in [&node.left, &node.right].into_iter().flatten() {
+                if IntervalTree::cmp_endbound(max, &child.value) == Less {
+                    max = &child.value;
+                }
+            }
+            assert_eq!(&node.value, max);
+        }
+
+        assert_valid_rec(&tree.root);
+    }
+
+    // Generates a tree of up to `n` intervals, the one at index `i` starting at `(i * 7) % width`
+    // and spanning up to `spread - 1` keys. The kind of every bound is picked by its key,
+    // and keys are even (see `test_util::bound`).
+    fn random_tree(n: i32, width: i32, spread: i32) -> IntervalTree<i32> {
+        (0..n)
+            .map(|i| {
+                let start = i * 7 % width;
+                let end = start + i % spread;
+                (bound(start, start), bound(end, end))
+            })
+            .filter(|range| IntervalTree::validate(range).is_ok())
+            .collect()
+    }
+
+    // Generates queries with every combination of bounds, starting around the keys of
+    // `random_tree(_, width, _)` and spanning up to `max_len` keys. Some of them are empty.
+    fn queries(width: i32, max_len: i32) -> impl Iterator<Item = Range<i32>> {
+        (-2..width + 2).flat_map(move |start| {
+            (0..=max_len).flat_map(move |len| (0..9).map(move |kind| (bound(start, kind / 3), bound(start + len, kind))))
+        })
+    }
+
+    // Brute-force oracle, scanning the intervals of the tree which satisfy `predicate`, in order.
+    fn brute_force(tree: &IntervalTree<i32>, predicate: impl Fn(&Range<i32>) -> bool) -> Vec<&Range<i32>> {
+        tree.iter().filter(|range| predicate(range)).collect()
+    }
+
+    #[test]
+    fn display_works_as_expected() {
+        let mut tree = IntervalTree::default();
+        assert_eq!(tree.to_string(), "Empty tree");
+
+        tree.insert((Included(2), Included(3)));
+        tree.insert((Unbounded, Excluded(1)));
+        assert_eq!(tree.to_string(), " { [2,3] (3]) left: { ]-∞,1[ (1[) } } ");
+
+        tree.insert((Excluded(3), Unbounded));
+        assert_eq!(
+            tree.to_string(),
+            " { [2,3] (∞) left: { ]-∞,1[ (1[) } right: { ]3,∞[ (∞) } } "
+        );
+    }
+
+    #[test]
+    fn deep_degenerate_tree_does_not_overflow_stack() {
+        let depth = 1_000_000;
+
+        // Build by hand a tree that is a single chain of right children,
+        // which a balanced insertion never produces.
+        // The index of end-points is built the same way.
+        let mut root = None;
+        let mut ends = None;
+        for i in (0..depth).rev() {
+            let mut node = Node::with_data((Included(i), Excluded(i + 1)), ());
+            node.right = root;
+            node.update();
+            root = Some(Box::new(node));
+
+            let mut end = Node::with_data((Included(i), Excluded(i + 1)), ());
+            end.right = ends;
+            end.update();
+            ends = Some(Box::new(end));
+        }
+        let mut tree = IntervalTree { root, ends, size: depth };
+        assert_eq!(tree.height(), depth);
+
+        assert_eq!(tree.iter().count(), depth);
+        assert_eq!(
+            tree.get_interval_overlaps(&(depth - 1..)),
+            vec![&(Included(depth - 1), Excluded(depth))]
+        );
+        assert_eq!(tree.get_point_overlaps(&(depth - 1)).count(), 1);
+        assert!(tree.contains_interval(&(depth - 3..depth)));
+        assert_eq!(tree.count_containing(&(depth - 1)), 1);
+        assert_eq!(tree.gaps(&(depth - 1..=depth)).count(), 1);
+        assert!(tree.to_string().ends_with(&format!(" }} {}", "} ".repeat(depth - 1))));
+
+        let cloned = tree.clone();
+        assert_eq!(cloned, tree);
+        drop(cloned);
+
+        tree.clear();
+        assert!(tree.is_empty());
+    }
+
     #[test]
     fn len_and_is_empty_works_as_expected() {
         let mut tree = IntervalTree::default();
@@ -1561,6 +4549,10 @@ mod tests {
         assert_eq!(tree.len(), 2);
         assert!(!tree.is_empty());
 
+        // Identical intervals are not inserted twice.
+        assert!(!tree.insert(key2));
+        assert_eq!(tree.len(), 2);
+
         tree.remove_random_leaf();
 
         assert_eq!(tree.len(), 1);
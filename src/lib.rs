@@ -7,12 +7,42 @@
 //! interval and the database (in order to find subsegments not covered), and the list of
 //! intervals in the database overlapping a queried interval.
 //!
-//! Note that any type satisfying the [`Ord`] trait can be stored in this tree.
+//! An interval map ([`interval_map::IntervalMap`]) built on the same data structure is also
+//! provided, in order to associate a value to every interval stored, as well as an interval multimap
+//! ([`interval_multimap::IntervalMultiMap`]) in which identical intervals can be stored multiple times.
+//! A range set ([`range_set::RangeSet`]) is also provided, which merges overlapping and adjacent
+//! intervals so as to represent a set of points, along with a piecewise-constant map
+//! ([`piecewise_map::PiecewiseMap`]) in which assigning a value to an interval overwrites its points.
+//! Finally, an additive map ([`additive_map::AdditiveMap`]) sums the weights added over intervals,
+//! to query the total weight at a point or over a window.
+//! Custom aggregates can also be maintained over the subtrees of an interval map,
+//! by implementing the [`augmented_map::Augment`] trait.
+//!
+//! Note that any type satisfying the [`Ord`] trait can be stored in this tree. Types also
+//! satisfying the [`measure::Measure`] trait, such as integers and instants, can be measured
+//! to compute how much of an interval is covered by the tree.
 //! 
 //! # Features
 //! 
 //! * `serde` — Enables using [Serde](http://serde.rs) to serialize/deserialize the interval tree.
 
+/// An additive interval map, summing the weights added over intervals.
+pub mod additive_map;
+/// Custom aggregates maintained over the subtrees of an interval map.
+pub mod augmented_map;
+/// An interval map implemented with a binary search tree.
+pub mod interval_map;
+/// An interval multimap implemented with a binary search tree.
+pub mod interval_multimap;
 /// An interval tree implemented with a binary search tree.
 pub mod interval_tree;
+/// A measure of the intervals of keys, to compute the total measure covered by a tree.
+pub mod measure;
 mod node;
+/// A piecewise-constant map stored as disjoint intervals in a binary search tree.
+pub mod piecewise_map;
+/// A set of points stored as disjoint intervals in a binary search tree.
+pub mod range_set;
+mod set_ops;
+#[cfg(test)]
+mod test_util;
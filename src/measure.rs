@@ -0,0 +1,107 @@
+use std::ops::Add;
+use std::time::{Duration, Instant, SystemTime};
+
+/// A measure of the intervals of keys, such as the length of a range of integers
+/// or the duration of a range of instants. It is used to compute the total measure
+/// covered by an [`IntervalTree`](crate::interval_tree::IntervalTree), as in
+/// [`IntervalTree::covered_measure`](crate::interval_tree::IntervalTree::covered_measure).
+///
+/// Keys are seen as points on a continuous line, so the measure of an interval only depends
+/// on the keys of its bounds, and not on whether they are included or excluded.
+/// For instance, both `[0, 5)` and `[0, 5]` have a length of `5`. Integers are measured
+/// with the unsigned integers of the same width, so that the length of any interval fits.
+///
+/// ```
+/// use std::ops::Add;
+/// use unbounded_interval_tree::interval_tree::IntervalTree;
+/// use unbounded_interval_tree::measure::Measure;
+///
+/// #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+/// struct Meters(u32);
+///
+/// impl Measure for Meters {
+///     type Length = u64;
+///
+///     fn length(start: &Self, end: &Self) -> u64 {
+///         u64::from(end.0 - start.0)
+///     }
+///
+///     fn ratio(part: &u64, whole: &u64) -> f64 {
+///         *part as f64 / *whole as f64
+///     }
+/// }
+///
+/// let tree = IntervalTree::from([Meters(0)..Meters(10), Meters(5)..Meters(20)]);
+///
+/// assert_eq!(tree.covered_measure(&(Meters(0)..Meters(40))), Some(20));
+/// assert_eq!(tree.coverage_ratio(&(Meters(0)..Meters(40))), Some(0.5));
+/// ```
+pub trait Measure {
+    /// The type of the measure of an interval, where `Length::default()` is a measure of zero.
+    type Length: Clone + Default + PartialOrd + Add<Output = Self::Length>;
+
+    /// Returns the measure of the interval between `start` and `end`, where `start <= end`.
+    fn length(start: &Self, end: &Self) -> Self::Length;
+
+    /// Returns the ratio of the measure `part` over the non-zero measure `whole`.
+    fn ratio(part: &Self::Length, whole: &Self::Length) -> f64;
+}
+
+macro_rules! impl_measure_for_integers {
+    ($($key:ty => $length:ty),*) => {
+        $(
+            impl Measure for $key {
+                type Length = $length;
+
+                fn length(start: &Self, end: &Self) -> $length {
+                    end.abs_diff(*start)
+                }
+
+                fn ratio(part: &$length, whole: &$length) -> f64 {
+                    *part as f64 / *whole as f64
+                }
+            }
+        )*
+    };
+}
+
+impl_measure_for_integers!(
+    i8 => u8, i16 => u16, i32 => u32, i64 => u64, i128 => u128, isize => usize,
+    u8 => u8, u16 => u16, u32 => u32, u64 => u64, u128 => u128, usize => usize
+);
+
+impl Measure for Duration {
+    type Length = Duration;
+
+    fn length(start: &Self, end: &Self) -> Duration {
+        end.saturating_sub(*start)
+    }
+
+    fn ratio(part: &Duration, whole: &Duration) -> f64 {
+        part.as_secs_f64() / whole.as_secs_f64()
+    }
+}
+
+impl Measure for SystemTime {
+    type Length = Duration;
+
+    fn length(start: &Self, end: &Self) -> Duration {
+        end.duration_since(*start).unwrap_or_default()
+    }
+
+    fn ratio(part: &Duration, whole: &Duration) -> f64 {
+        <Duration as Measure>::ratio(part, whole)
+    }
+}
+
+impl Measure for Instant {
+    type Length = Duration;
+
+    fn length(start: &Self, end: &Self) -> Duration {
+        end.saturating_duration_since(*start)
+    }
+
+    fn ratio(part: &Duration, whole: &Duration) -> f64 {
+        <Duration as Measure>::ratio(part, whole)
+    }
+}
@@ -1,4 +1,11 @@
+use crate::augmented_map::Augment;
+#[cfg(any(feature="serde", test))]
+use crate::interval_tree::IntervalTree;
+
+#[cfg(any(feature="serde", test))]
+use std::cmp::Ordering::Less;
 use std::fmt;
+use std::mem;
 use std::ops::Bound;
 use std::ops::Bound::*;
 #[cfg(any(feature="serde", test))]
@@ -7,111 +14,493 @@ use serde::{Serialize, Deserialize};
 pub(crate) type Range<K> = (Bound<K>, Bound<K>);
 
 #[cfg_attr(any(feature="serde", test), derive(Serialize, Deserialize))]
-#[derive(Clone, Debug, PartialEq)]
-pub(crate) struct Node<K> {
+#[cfg_attr(
+    any(feature="serde", test),
+    serde(
+        try_from = "SerializedNode<K, V, A>",
+        bound(
+            serialize = "K: Serialize, V: Serialize",
+            deserialize = "K: Deserialize<'de> + Ord + Clone, V: Deserialize<'de>, A: Augment<K, V>"
+        )
+    )
+)]
+#[derive(Debug)]
+pub(crate) struct Node<K, V = (), A = ()> {
     pub key: Range<K>,
+    // Data associated with the key. It is omitted when serializing a data-less node (e.g. `()`).
+    #[cfg_attr(any(feature="serde", test), serde(skip_serializing_if = "is_zero_sized"))]
+    pub data: V,
     pub value: Bound<K>, // Max end-point.
-    pub left: Option<Box<Node<K>>>,
-    pub right: Option<Box<Node<K>>>,
+    pub left: Option<Box<Node<K, V, A>>>,
+    pub right: Option<Box<Node<K, V, A>>>,
+    // Height of the subtree (a leaf has a height of 1), used to keep the tree balanced.
+    // It is derived from the shape of the tree, so it is not serialized.
+    #[cfg_attr(any(feature="serde", test), serde(skip_serializing))]
+    pub height: usize,
+    // Aggregate of the intervals of the subtree, along with their data (see [`Augment`]).
+    // It is derived from the subtree, so it is not serialized either.
+    #[cfg_attr(any(feature="serde", test), serde(skip_serializing))]
+    pub aggregate: A,
+}
+
+#[cfg(any(feature="serde", test))]
+fn is_zero_sized<V>(_: &V) -> bool {
+    mem::size_of::<V>() == 0
 }
 
-impl<K> fmt::Display for Node<K>
+/// Serialized form of a [`Node`], from which the max end-point, the height and the aggregate are recomputed.
+/// The serialized max end-point is ignored, so that it cannot disagree with the subtree.
+#[cfg(any(feature="serde", test))]
+#[derive(Deserialize)]
+#[serde(bound(deserialize = "K: Deserialize<'de> + Ord + Clone, V: Deserialize<'de>, A: Augment<K, V>"))]
+struct SerializedNode<K, V, A> {
+    key: Range<K>,
+    data: Option<V>,
+    left: Option<Box<Node<K, V, A>>>,
+    right: Option<Box<Node<K, V, A>>>,
+}
+
+#[cfg(any(feature="serde", test))]
+impl<'de, K, V, A> TryFrom<SerializedNode<K, V, A>> for Node<K, V, A>
 where
-    K: fmt::Display,
+    K: Ord + Clone,
+    V: Deserialize<'de>,
+    A: Augment<K, V>,
 {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let start = match self.key.0 {
-            Included(ref x) => format!("[{}", x),
-            Excluded(ref x) => format!("]{}", x),
-            Unbounded => String::from("]-∞"),
-        };
-        let end = match self.key.1 {
-            Included(ref x) => format!("{}]", x),
-            Excluded(ref x) => format!("{}[", x),
-            Unbounded => format!("∞["),
+    type Error = serde::de::value::Error;
+
+    fn try_from(node: SerializedNode<K, V, A>) -> Result<Self, Self::Error> {
+        use serde::de::{Error, IntoDeserializer};
+
+        // Invalid intervals cannot be inserted, so they cannot be deserialized either.
+        IntervalTree::validate(&node.key).map_err(Error::custom)?;
+
+        // Data-less nodes are serialized without data, in which case
+        // it is deserialized from a unit value (e.g. `()`).
+        let data = match node.data {
+            Some(data) => data,
+            None => V::deserialize(().into_deserializer())?,
         };
-        let value = match self.value {
-            Included(ref x) => format!("{}]", x),
-            Excluded(ref x) => format!("{}[", x),
-            Unbounded => String::from("∞"),
+        let mut node = Node {
+            key: node.key,
+            data,
+            value: Unbounded,
+            left: node.left,
+            right: node.right,
+            height: 1,
+            aggregate: A::identity(),
         };
+        node.update();
+
+        // Deserialized trees must be balanced as well, so that their operations stay logarithmic.
+        if Self::height(&node.left).abs_diff(Self::height(&node.right)) > 1 {
+            return Err(Error::custom("the tree is unbalanced"));
+        }
+
+        Ok(node)
+    }
+}
 
-        if self.left.is_none() && self.right.is_none() {
-            write!(f, " {{ {},{} ({}) }} ", start, end, value)
-        } else if self.left.is_none() {
-            write!(
-                f,
-                " {{ {},{} ({}) right:{}}} ",
-                start,
-                end,
-                value,
-                self.right.as_ref().unwrap()
-            )
-        } else if self.right.is_none() {
-            write!(
-                f,
-                " {{ {},{} ({}) left:{}}} ",
-                start,
-                end,
-                value,
-                self.left.as_ref().unwrap()
-            )
-        } else {
-            write!(
-                f,
-                " {{ {},{} ({}) left:{}right:{}}} ",
-                start,
-                end,
-                value,
-                self.left.as_ref().unwrap(),
-                self.right.as_ref().unwrap()
-            )
+/// Serialized form of a tree of [`Node`]s. Its size is recomputed from its nodes,
+/// so the serialized size is ignored.
+#[cfg(any(feature="serde", test))]
+#[derive(Deserialize)]
+#[serde(bound(deserialize = "K: Deserialize<'de> + Ord + Clone, V: Deserialize<'de>, A: Augment<K, V>"))]
+pub(crate) struct SerializedTree<K, V, A = ()> {
+    pub root: Option<Box<Node<K, V, A>>>,
+}
+
+#[cfg(any(feature="serde", test))]
+impl<K, V, A> SerializedTree<K, V, A>
+where
+    K: Ord,
+{
+    /// Returns the number of intervals of the tree, or an error if they are not sorted.
+    pub fn size(&self) -> Result<usize, serde::de::value::Error> {
+        use serde::de::Error;
+
+        let mut size = 0;
+        let mut prev: Option<&Range<K>> = None;
+        for node in NodeIter::new(&self.root) {
+            if prev.is_some_and(|prev| IntervalTree::cmp(prev, &node.key) != Less) {
+                return Err(Error::custom("the intervals are not sorted"));
+            }
+            prev = Some(&node.key);
+            size += 1;
         }
+
+        Ok(size)
+    }
+}
+
+// Nodes are cloned in postorder rather than recursively, so that deep trees
+// cannot overflow the stack.
+impl<K, V, A> Clone for Node<K, V, A>
+where
+    K: Clone,
+    V: Clone,
+    A: Clone,
+{
+    fn clone(&self) -> Self {
+        let mut to_visit = vec![(self, false)];
+        // Clones of the subtrees visited, whose parent has not been cloned yet.
+        let mut cloned: Vec<Box<Node<K, V, A>>> = vec![];
+
+        while let Some((node, children_cloned)) = to_visit.pop() {
+            if !children_cloned {
+                to_visit.push((node, true));
+                to_visit.extend(node.right.as_deref().map(|right| (right, false)));
+                to_visit.extend(node.left.as_deref().map(|left| (left, false)));
+                continue;
+            }
+
+            // The clone of the right subtree was pushed after the one of the left subtree.
+            let right = node.right.as_ref().map(|_| cloned.pop().unwrap());
+            let left = node.left.as_ref().map(|_| cloned.pop().unwrap());
+            cloned.push(Box::new(Node {
+                key: node.key.clone(),
+                data: node.data.clone(),
+                value: node.value.clone(),
+                left,
+                right,
+                height: node.height,
+                aggregate: node.aggregate.clone(),
+            }));
+        }
+
+        *cloned.pop().unwrap()
+    }
+}
+
+// The height and the aggregate are derived from the tree, so they are not compared.
+// Nodes are compared one pair at a time rather than recursively, so that deep trees
+// cannot overflow the stack.
+impl<K, V, A> PartialEq for Node<K, V, A>
+where
+    K: PartialEq,
+    V: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        let mut to_compare = vec![(self, other)];
+
+        while let Some((node, other)) = to_compare.pop() {
+            if node.key != other.key || node.data != other.data || node.value != other.value {
+                return false;
+            }
+
+            for children in [(&node.left, &other.left), (&node.right, &other.right)] {
+                match children {
+                    (None, None) => {}
+                    (Some(child), Some(other_child)) => to_compare.push((child, other_child)),
+                    _ => return false,
+                }
+            }
+        }
+
+        true
+    }
+}
+
+// The tree is written in preorder rather than recursively, so that deep trees
+// cannot overflow the stack.
+impl<K, V, A> fmt::Display for Node<K, V, A>
+where
+    K: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Text to write, followed by the subtree to write after it.
+        let mut to_write = vec![("", Some(self))];
+
+        while let Some((text, node)) = to_write.pop() {
+            write!(f, "{}", text)?;
+
+            let node = match node {
+                None => continue,
+                Some(node) => node,
+            };
+
+            let start = match node.key.0 {
+                Included(ref x) => format!("[{}", x),
+                Excluded(ref x) => format!("]{}", x),
+                Unbounded => String::from("]-∞"),
+            };
+            let end = match node.key.1 {
+                Included(ref x) => format!("{}]", x),
+                Excluded(ref x) => format!("{}[", x),
+                Unbounded => "∞[".to_string(),
+            };
+            let value = match node.value {
+                Included(ref x) => format!("{}]", x),
+                Excluded(ref x) => format!("{}[", x),
+                Unbounded => String::from("∞"),
+            };
+
+            write!(f, " {{ {},{} ({})", start, end, value)?;
+            match (&node.left, &node.right) {
+                (None, None) => write!(f, " }} ")?,
+                (left, right) => {
+                    write!(f, " ")?;
+                    to_write.push(("} ", None));
+                    to_write.extend(right.as_deref().map(|right| ("right:", Some(right))));
+                    to_write.extend(left.as_deref().map(|left| ("left:", Some(left))));
+                }
+            }
+        }
+
+        Ok(())
     }
 }
 
 impl<K> Node<K> {
+    #[cfg(test)]
     pub fn new(range: Range<K>) -> Node<K>
     where
         K: Clone,
+    {
+        Self::with_data(range, ())
+    }
+}
+
+impl<K, V, A> Node<K, V, A> {
+    pub fn with_data(range: Range<K>, data: V) -> Node<K, V, A>
+    where
+        K: Clone,
+        A: Augment<K, V>,
     {
         let max = range.1.clone();
+        let aggregate = A::lift(&range, &data);
 
         Node {
             key: range,
+            data,
             value: max,
             left: None,
             right: None,
+            height: 1,
+            aggregate,
+        }
+    }
+
+    /// Returns the height of the given subtree, where an empty subtree has a height of 0.
+    pub fn height(node: &Option<Box<Node<K, V, A>>>) -> usize {
+        node.as_ref().map_or(0, |node| node.height)
+    }
+
+    /// Drops the tree rooted at `root` one node at a time. The compiler-generated drop
+    /// of a `Box<Node>` is recursive, which would overflow the stack on deep trees.
+    pub fn drop_tree(root: Option<Box<Node<K, V, A>>>) {
+        let mut to_drop: Vec<_> = root.into_iter().collect();
+
+        while let Some(mut node) = to_drop.pop() {
+            to_drop.extend(node.left.take());
+            to_drop.extend(node.right.take());
+        }
+    }
+
+    /// Recomputes the max end-point of the subtree rooted at this node,
+    /// assuming the values stored by its children are up-to-date.
+    pub fn update_value(&mut self)
+    where
+        K: PartialOrd + Clone,
+    {
+        let mut max = &self.key.1;
+        for child in [&self.left, &self.right].into_iter().flatten() {
+            if Self::is_endbound_less(max, &child.value) {
+                max = &child.value;
+            }
+        }
+        self.value = max.clone();
+    }
+
+    fn update_height(&mut self) {
+        self.height = 1 + Self::height(&self.left).max(Self::height(&self.right));
+    }
+
+    /// Recomputes the aggregate of the subtree rooted at this node, combining inorder
+    /// the aggregates of its children with the one of its own interval,
+    /// assuming the aggregates stored by its children are up-to-date.
+    pub fn update_aggregate(&mut self)
+    where
+        A: Augment<K, V>,
+    {
+        let own = A::lift(&self.key, &self.data);
+        let mut aggregate = match self.left {
+            Some(ref left) => left.aggregate.combine(&own),
+            None => own,
+        };
+        if let Some(ref right) = self.right {
+            aggregate = aggregate.combine(&right.aggregate);
+        }
+        self.aggregate = aggregate;
+    }
+
+    /// Recomputes the max end-point, the height and the aggregate of this node,
+    /// assuming that its children are up-to-date.
+    pub fn update(&mut self)
+    where
+        K: PartialOrd + Clone,
+        A: Augment<K, V>,
+    {
+        self.update_value();
+        self.update_height();
+        self.update_aggregate();
+    }
+
+    /// Restores the AVL invariant at this node (the heights of both subtrees differ by at most one),
+    /// assuming that its subtrees are balanced and that their heights differ by at most two.
+    /// The max end-point, the height and the aggregate of all rotated nodes are updated accordingly.
+    pub fn rebalance(node: &mut Box<Node<K, V, A>>)
+    where
+        K: PartialOrd + Clone,
+        A: Augment<K, V>,
+    {
+        node.update();
+
+        let left_height = Self::height(&node.left);
+        let right_height = Self::height(&node.right);
+
+        if left_height > right_height + 1 {
+            let left = node.left.as_mut().unwrap();
+            if Self::height(&left.left) < Self::height(&left.right) {
+                Self::rotate_left(left);
+            }
+            Self::rotate_right(node);
+        } else if right_height > left_height + 1 {
+            let right = node.right.as_mut().unwrap();
+            if Self::height(&right.right) < Self::height(&right.left) {
+                Self::rotate_right(right);
+            }
+            Self::rotate_left(node);
         }
     }
 
-    pub fn is_leaf(&self) -> bool {
-        self.left.is_none() && self.right.is_none()
+    //       node              left
+    //      /    \            /    \
+    //    left    c    ->    a     node
+    //   /    \                   /    \
+    //  a      b                 b      c
+    fn rotate_right(node: &mut Box<Node<K, V, A>>)
+    where
+        K: PartialOrd + Clone,
+        A: Augment<K, V>,
+    {
+        let mut left = node.left.take().unwrap();
+        node.left = left.right.take();
+        node.update();
+
+        mem::swap(node, &mut left);
+        node.right = Some(left);
+        node.update();
     }
 
-    pub fn maybe_update_value(&mut self, inserted_max: &Bound<K>)
+    //     node                  right
+    //    /    \                /     \
+    //   a     right    ->    node     c
+    //        /     \        /    \
+    //       b       c      a      b
+    fn rotate_left(node: &mut Box<Node<K, V, A>>)
     where
         K: PartialOrd + Clone,
+        A: Augment<K, V>,
     {
-        let self_max_q = match &self.value {
+        let mut right = node.right.take().unwrap();
+        node.right = right.left.take();
+        node.update();
+
+        mem::swap(node, &mut right);
+        node.left = Some(right);
+        node.update();
+    }
+
+    fn is_endbound_less(e1: &Bound<K>, e2: &Bound<K>) -> bool
+    where
+        K: PartialOrd,
+    {
+        let e1 = match e1 {
             Included(x) => Some((x, 2)),
             Excluded(x) => Some((x, 1)),
             Unbounded => None,
         };
-        let inserted_max_q = match inserted_max {
+        let e2 = match e2 {
             Included(x) => Some((x, 2)),
             Excluded(x) => Some((x, 1)),
             Unbounded => None,
         };
-        match (self_max_q, inserted_max_q) {
-            (None, _) => {}
-            (_, None) => self.value = Unbounded,
-            (Some(self_max_q), Some(inserted_max_q)) => {
-                if self_max_q < inserted_max_q {
-                    self.value = inserted_max.clone();
-                }
-            }
-        };
+        match (e1, e2) {
+            (None, _) => false,
+            (_, None) => true,
+            (Some(e1), Some(e2)) => e1 < e2,
+        }
+    }
+}
+
+/// An inorder iterator through the nodes of a tree.
+pub(crate) struct NodeIter<'a, K, V, A = ()> {
+    to_visit: Vec<&'a Node<K, V, A>>,
+    curr: &'a Option<Box<Node<K, V, A>>>,
+}
+
+impl<'a, K, V, A> NodeIter<'a, K, V, A> {
+    pub fn new(root: &'a Option<Box<Node<K, V, A>>>) -> Self {
+        NodeIter {
+            to_visit: vec![],
+            curr: root,
+        }
+    }
+}
+
+impl<'a, K, V, A> Iterator for NodeIter<'a, K, V, A> {
+    type Item = &'a Node<K, V, A>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(node) = self.curr {
+            self.to_visit.push(node);
+            self.curr = &node.left;
+        }
+
+        let visited = self.to_visit.pop()?;
+        self.curr = &visited.right;
+        Some(visited)
+    }
+}
+
+// A node split into its key, its data and its right subtree.
+pub(crate) type SplitNode<'a, K, V> = (&'a Range<K>, &'a mut V, &'a mut Option<Box<Node<K, V>>>);
+
+/// An inorder iterator through the nodes of a tree, giving mutable access to their data.
+pub(crate) struct NodeIterMut<'a, K, V> {
+    to_visit: Vec<SplitNode<'a, K, V>>,
+    curr: Option<&'a mut Box<Node<K, V>>>,
+}
+
+impl<'a, K, V> NodeIterMut<'a, K, V> {
+    pub fn new(root: &'a mut Option<Box<Node<K, V>>>) -> Self {
+        NodeIterMut {
+            to_visit: vec![],
+            curr: root.as_mut(),
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for NodeIterMut<'a, K, V> {
+    type Item = (&'a Range<K>, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(node) = self.curr.take() {
+            let Node {
+                key,
+                data,
+                left,
+                right,
+                ..
+            } = &mut **node;
+            self.to_visit.push((key, data, right));
+            self.curr = left.as_mut();
+        }
+
+        let (key, data, right) = self.to_visit.pop()?;
+        self.curr = right.as_mut();
+        Some((key, data))
     }
 }
 
@@ -213,4 +602,31 @@ mod tests {
 	let deserialized_node = from_str(&serialized_value).unwrap();
 	assert_eq!(expected_node, deserialized_node);
     }
+
+    #[test]
+    fn deserialize_recomputes_value() {
+	let mut expected_node = Node::new((Included(2), Included(4)));
+	expected_node.left = Some(Box::new(Node::new((Included(1), Unbounded))));
+	expected_node.update_value();
+	let value = json!({
+	    "key": [
+		{"Included": 2},
+		{"Included": 4},
+	    ],
+	    "left": {
+		"key": [
+		    {"Included": 1},
+		    "Unbounded",
+		],
+		"left": null,
+		"right": null,
+		"value": {"Included": 1},
+	    },
+	    "right": null,
+	    "value": {"Excluded": 0},
+	});
+	let deserialized_node: Node<i32> = from_str(&value.to_string()).unwrap();
+	assert_eq!(deserialized_node.value, Unbounded);
+	assert_eq!(expected_node, deserialized_node);
+    }
 }
@@ -0,0 +1,569 @@
+use crate::interval_map::IntervalMap;
+use crate::interval_tree::{IntervalError, IntervalTree, OverlapIter};
+use crate::node::{Node, NodeIter, Range};
+use crate::set_ops::Cut;
+
+use std::borrow::Borrow;
+use std::cmp::Ordering::*;
+use std::fmt;
+use std::ops::Bound::*;
+use std::ops::RangeBounds;
+#[cfg(any(feature="serde", test))]
+use serde::{Serialize, Deserialize};
+
+/// A piecewise-constant map, associating a value to every point covered by one of its pieces.
+///
+/// Assigning a value to an interval overwrites the values previously assigned to its points,
+/// splitting the pieces partially overlapping with it. Adjacent pieces with equal values
+/// are merged, so that the pieces stored are always disjoint, and every piece is a maximal run
+/// of points with the same value.
+/// It is built on the same data structure as [`IntervalTree`].
+///
+/// ```
+/// use std::ops::Bound::{Included, Excluded, Unbounded};
+/// use unbounded_interval_tree::piecewise_map::PiecewiseMap;
+///
+/// let mut config = PiecewiseMap::default();
+///
+/// config.assign(.., "default");
+/// config.assign(10..20, "maintenance");
+/// config.assign(20..30, "maintenance");
+///
+/// assert_eq!(config.get(&15), Some(&"maintenance"));
+/// assert_eq!(config.iter().collect::<Vec<_>>(), vec![
+///     (&(Unbounded, Excluded(10)), &"default"),
+///     (&(Included(10), Excluded(30)), &"maintenance"),
+///     (&(Included(30), Unbounded), &"default"),
+/// ]);
+/// ```
+#[cfg_attr(any(feature="serde", test), derive(Serialize, Deserialize))]
+#[cfg_attr(
+    any(feature="serde", test),
+    serde(
+        from = "IntervalMap<K, V>",
+        bound(deserialize = "K: Deserialize<'de> + Ord + Clone, V: Deserialize<'de> + Clone + PartialEq")
+    )
+)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct PiecewiseMap<K, V> {
+    root: Option<Box<Node<K, V>>>,
+    size: usize,
+}
+
+impl<K, V> fmt::Display for PiecewiseMap<K, V>
+where
+    K: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.root {
+            Some(ref root) => write!(f, "{}", root),
+            None => write!(f, "Empty map"),
+        }
+    }
+}
+
+impl<K, V> Default for PiecewiseMap<K, V> {
+    fn default() -> PiecewiseMap<K, V> {
+        PiecewiseMap {
+            root: None,
+            size: 0,
+        }
+    }
+}
+
+impl<K, V> Drop for PiecewiseMap<K, V> {
+    fn drop(&mut self) {
+        Node::drop_tree(self.root.take());
+    }
+}
+
+/// Creates a [`PiecewiseMap`] from an iterator of pairs of elements satisfying the
+/// [`RangeBounds`] trait and their values, which are assigned in order.
+impl<K, V, R> FromIterator<(R, V)> for PiecewiseMap<K, V>
+where
+    K: Ord + Clone,
+    V: Clone + PartialEq,
+    R: RangeBounds<K>,
+{
+    fn from_iter<T: IntoIterator<Item = (R, V)>>(iter: T) -> Self {
+        let mut piecewise_map = Self::default();
+
+        for (interval, value) in iter {
+            piecewise_map.assign(interval, value);
+        }
+
+        piecewise_map
+    }
+}
+
+impl<K, V, R, const N: usize> From<[(R, V); N]> for PiecewiseMap<K, V>
+where
+    K: Ord + Clone,
+    V: Clone + PartialEq,
+    R: RangeBounds<K>,
+{
+    fn from(entries: [(R, V); N]) -> Self {
+        entries.into_iter().collect()
+    }
+}
+
+/// Creates a [`PiecewiseMap`] by assigning the entries of an [`IntervalMap`] inorder.
+/// Where intervals overlap, the value of the interval coming last in the map is kept.
+///
+/// # Examples
+///
+/// ```
+/// use std::ops::Bound::{Included, Excluded};
+/// use unbounded_interval_tree::interval_map::IntervalMap;
+/// use unbounded_interval_tree::piecewise_map::PiecewiseMap;
+///
+/// let map = IntervalMap::from([(0..10, 'a'), (5..15, 'b')]);
+/// let piecewise_map = PiecewiseMap::from(map);
+///
+/// assert_eq!(piecewise_map.iter().collect::<Vec<_>>(), vec![
+///     (&(Included(0), Excluded(5)), &'a'),
+///     (&(Included(5), Excluded(15)), &'b'),
+/// ]);
+/// ```
+impl<K, V> From<IntervalMap<K, V>> for PiecewiseMap<K, V>
+where
+    K: Ord + Clone,
+    V: Clone + PartialEq,
+{
+    fn from(map: IntervalMap<K, V>) -> Self {
+        map.iter().map(|(range, value)| (range.clone(), value.clone())).collect()
+    }
+}
+
+impl<K, V> PiecewiseMap<K, V> {
+    /// Produces an inorder iterator over the pieces of the map, along with their values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::ops::Bound::{Included, Excluded};
+    /// use unbounded_interval_tree::piecewise_map::PiecewiseMap;
+    ///
+    /// let map = PiecewiseMap::from([(0..10, 'a'), (5..8, 'b')]);
+    ///
+    /// let mut iter = map.iter();
+    /// assert_eq!(iter.next(), Some((&(Included(0), Excluded(5)), &'a')));
+    /// assert_eq!(iter.next(), Some((&(Included(5), Excluded(8)), &'b')));
+    /// assert_eq!(iter.next(), Some((&(Included(8), Excluded(10)), &'a')));
+    /// assert_eq!(iter.next(), None);
+    /// ```
+    pub fn iter<'a>(&'a self) -> PiecewiseMapIter<'a, K, V> {
+        PiecewiseMapIter {
+            inner: NodeIter::new(&self.root),
+        }
+    }
+
+    /// Assigns `value` to all the points of the interval `range`, overwriting the values
+    /// previously assigned to them. The pieces partially overlapping with `range` are split,
+    /// and the resulting piece is merged with the adjacent pieces having an equal value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::ops::Bound::{Included, Excluded};
+    /// use unbounded_interval_tree::piecewise_map::PiecewiseMap;
+    ///
+    /// let mut map = PiecewiseMap::default();
+    ///
+    /// map.assign(0..10, 1);
+    /// map.assign(3..6, 2);
+    /// assert_eq!(map.len(), 3);
+    ///
+    /// map.assign(3..6, 1);
+    /// assert_eq!(map.iter().collect::<Vec<_>>(), vec![(&(Included(0), Excluded(10)), &1)]);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is inverted or empty, like [`IntervalTree::insert`].
+    /// See [`PiecewiseMap::try_assign`] to handle invalid intervals instead.
+    pub fn assign<R>(&mut self, range: R, value: V)
+    where
+        K: Ord + Clone,
+        V: Clone + PartialEq,
+        R: RangeBounds<K>,
+    {
+        if let Err(err) = self.try_assign(range, value) {
+            panic!("cannot assign to an invalid interval: {}", err);
+        }
+    }
+
+    /// Assigns `value` to all the points of the interval `range`, like [`PiecewiseMap::assign`],
+    /// unless `range` is invalid. See [`IntervalTree::try_insert`] for more details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unbounded_interval_tree::interval_tree::IntervalError;
+    /// use unbounded_interval_tree::piecewise_map::PiecewiseMap;
+    ///
+    /// let mut map = PiecewiseMap::default();
+    ///
+    /// assert_eq!(map.try_assign(0..10, 'a'), Ok(()));
+    /// assert_eq!(map.try_assign(10..0, 'b'), Err(IntervalError::Inverted));
+    /// assert_eq!(map.len(), 1);
+    /// ```
+    pub fn try_assign<R>(&mut self, range: R, value: V) -> Result<(), IntervalError>
+    where
+        K: Ord + Clone,
+        V: Clone + PartialEq,
+        R: RangeBounds<K>,
+    {
+        let range = (range.start_bound().cloned(), range.end_bound().cloned());
+        IntervalTree::validate(&range)?;
+        let (mut start, mut end) = range;
+
+        self.clear_cuts(Cut::from_start(start.as_ref()), Cut::from_end(end.as_ref()));
+
+        // Once `range` is cleared, the only pieces touching it are adjacent to one of its ends.
+        let start_cut = Cut::from_start(start.as_ref());
+        let before = IntervalTree::find_disjoint(&self.root, start_cut, start_cut, true)
+            .filter(|node| node.data == value)
+            .map(|node| node.key.clone());
+        let end_cut = Cut::from_end(end.as_ref());
+        let after = IntervalTree::find_disjoint(&self.root, end_cut, end_cut, true)
+            .filter(|node| node.data == value)
+            .map(|node| node.key.clone());
+
+        if let Some(before) = before {
+            IntervalTree::remove_rec(&mut self.root, &before);
+            self.size -= 1;
+            start = before.0;
+        }
+        if let Some(after) = after {
+            IntervalTree::remove_rec(&mut self.root, &after);
+            self.size -= 1;
+            end = after.1;
+        }
+
+        IntervalTree::insert_rec(&mut self.root, (start, end), value);
+        self.size += 1;
+
+        Ok(())
+    }
+
+    /// Returns the value assigned to the point `p`, if any.
+    ///
+    /// The given point may be of a borrowed form of the stored type `K`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unbounded_interval_tree::piecewise_map::PiecewiseMap;
+    ///
+    /// let mut map = PiecewiseMap::default();
+    ///
+    /// map.assign(0..10, 'a');
+    /// map.assign(10.., 'b');
+    ///
+    /// assert_eq!(map.get(&-1), None);
+    /// assert_eq!(map.get(&9), Some(&'a'));
+    /// assert_eq!(map.get(&10), Some(&'b'));
+    /// ```
+    pub fn get<Q>(&self, p: &Q) -> Option<&V>
+    where
+        K: Ord + Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        OverlapIter::new(&self.root, Included(p), Included(p))
+            .next()
+            .map(|node| &node.data)
+    }
+
+    /// Returns an inorder iterator over the pieces of the map overlapping with the given `range`,
+    /// clipped to `range`, along with their values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::ops::Bound::{Included, Excluded};
+    /// use unbounded_interval_tree::piecewise_map::PiecewiseMap;
+    ///
+    /// let map = PiecewiseMap::from([(0..10, 'a'), (10..20, 'b'), (20..30, 'c')]);
+    ///
+    /// assert_eq!(map.iter_range(&(5..=15)).collect::<Vec<_>>(), vec![
+    ///     ((Included(&5), Excluded(&10)), &'a'),
+    ///     ((Included(&10), Included(&15)), &'b'),
+    /// ]);
+    /// ```
+    pub fn iter_range<'a, R>(&'a self, range: &'a R) -> impl Iterator<Item = (Range<&'a K>, &'a V)>
+    where
+        K: Ord,
+        R: RangeBounds<K>,
+    {
+        let (start, end) = (range.start_bound(), range.end_bound());
+
+        OverlapIter::new(&self.root, start, end).map(move |node| {
+            let (piece_start, piece_end) = (node.key.0.as_ref(), node.key.1.as_ref());
+
+            // The ends of the clipped piece are the innermost ones between the piece and `range`.
+            let clipped_start = match IntervalTree::cmp(&(piece_start, Unbounded), &(start, Unbounded)) {
+                Less => start,
+                _ => piece_start,
+            };
+            let clipped_end = match IntervalTree::cmp_endbound(&piece_end, &end) {
+                Greater => end,
+                _ => piece_end,
+            };
+
+            ((clipped_start, clipped_end), &node.data)
+        })
+    }
+
+    /// Removes the values assigned to all the points of the interval `range`.
+    /// The pieces partially overlapping with `range` are split accordingly.
+    ///
+    /// Returns whether the map changed, that is whether some points of `range` had a value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::ops::Bound::{Included, Excluded};
+    /// use unbounded_interval_tree::piecewise_map::PiecewiseMap;
+    ///
+    /// let mut map = PiecewiseMap::from([(0..10, 'a')]);
+    ///
+    /// assert!(map.clear_range(3..5));
+    /// assert!(!map.clear_range(3..5));
+    ///
+    /// assert_eq!(map.iter().collect::<Vec<_>>(), vec![
+    ///     (&(Included(0), Excluded(3)), &'a'),
+    ///     (&(Included(5), Excluded(10)), &'a'),
+    /// ]);
+    /// ```
+    pub fn clear_range<R>(&mut self, range: R) -> bool
+    where
+        K: Ord + Clone,
+        V: Clone,
+        R: RangeBounds<K>,
+    {
+        self.clear_cuts(Cut::from_start(range.start_bound()), Cut::from_end(range.end_bound()))
+    }
+
+    // Removes the values assigned to the points between the cuts `start` and `end`,
+    // and returns whether some points had a value.
+    fn clear_cuts(&mut self, start: Cut<&K>, end: Cut<&K>) -> bool
+    where
+        K: Ord + Clone,
+        V: Clone,
+    {
+        // An inverted or empty range contains no point to clear.
+        if start >= end {
+            return false;
+        }
+
+        let mut removed = false;
+        while let Some(overlap) = IntervalTree::find_disjoint(&self.root, start, end, false) {
+            let overlap = overlap.key.clone();
+            let (overlap, value) = match IntervalTree::remove_rec(&mut self.root, &overlap) {
+                Some(entry) => entry,
+                None => unreachable!("the piece found is in the map"),
+            };
+            self.size -= 1;
+            removed = true;
+
+            // Keep the parts of the removed piece lying outside of the cleared range.
+            let overlap_start = Cut::from_start(overlap.0);
+            let overlap_end = Cut::from_end(overlap.1);
+            if overlap_start.as_ref() < start {
+                IntervalTree::insert_rec(
+                    &mut self.root,
+                    (overlap_start.into_start(), start.cloned().into_end()),
+                    value.clone(),
+                );
+                self.size += 1;
+            }
+            if end < overlap_end.as_ref() {
+                IntervalTree::insert_rec(
+                    &mut self.root,
+                    (end.cloned().into_start(), overlap_end.into_end()),
+                    value,
+                );
+                self.size += 1;
+            }
+        }
+
+        removed
+    }
+
+    /// Returns the number of pieces in the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unbounded_interval_tree::piecewise_map::PiecewiseMap;
+    ///
+    /// let map = PiecewiseMap::from([(0..10, 'a'), (10..20, 'a'), (30..40, 'b')]);
+    ///
+    /// assert_eq!(map.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.size
+    }
+
+    /// Returns `true` if no point has a value in the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unbounded_interval_tree::piecewise_map::PiecewiseMap;
+    ///
+    /// let mut map = PiecewiseMap::default();
+    ///
+    /// assert!(map.is_empty());
+    ///
+    /// map.assign(0..10, 'a');
+    ///
+    /// assert!(!map.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Clear the map, removing all pieces.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unbounded_interval_tree::piecewise_map::PiecewiseMap;
+    ///
+    /// let mut map = PiecewiseMap::from([(0..10, 'a'), (20..30, 'b')]);
+    ///
+    /// map.clear();
+    ///
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn clear(&mut self) {
+        Node::drop_tree(self.root.take());
+        self.size = 0;
+    }
+}
+
+/// An inorder interator through the pieces of a piecewise map.
+pub struct PiecewiseMapIter<'a, K, V> {
+    inner: NodeIter<'a, K, V>,
+}
+
+impl<'a, K, V> Iterator for PiecewiseMapIter<'a, K, V> {
+    type Item = (&'a Range<K>, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|node| (&node.key, &node.data))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{intervals, points};
+    use serde_json::{from_str, to_string};
+
+    #[test]
+    fn serialize_deserialize_identity() {
+        let mut map = PiecewiseMap::default();
+        let serialized_empty_map = to_string(&map).unwrap();
+        let deserialized_empty_map = from_str(&serialized_empty_map).unwrap();
+        assert_eq!(map, deserialized_empty_map);
+
+        map.assign((Unbounded, Excluded(3)), 'a');
+        map.assign((Excluded(5), Included(8)), 'b');
+        let serialized_map = to_string(&map).unwrap();
+        let deserialized_map = from_str(&serialized_map).unwrap();
+        assert_eq!(map, deserialized_map);
+
+        // A serialized interval map is deserialized into its canonical form.
+        let interval_map = IntervalMap::from([(0..5, 'a'), (5..10, 'a')]);
+        let deserialized_map: PiecewiseMap<_, _> = from_str(&to_string(&interval_map).unwrap()).unwrap();
+        assert_eq!(deserialized_map, PiecewiseMap::from([(0..10, 'a')]));
+    }
+
+    #[test]
+    fn assign_splits_and_coalesces_pieces() {
+        let mut map = PiecewiseMap::default();
+
+        map.assign(.., 0);
+        map.assign((Excluded(10), Included(20)), 1);
+        assert_eq!(
+            map.iter().collect::<Vec<_>>(),
+            vec![
+                (&(Unbounded, Included(10)), &0),
+                (&(Excluded(10), Included(20)), &1),
+                (&(Excluded(20), Unbounded), &0),
+            ]
+        );
+
+        // Included+Excluded at the same point is adjacent, so pieces are merged.
+        map.assign((Included(5), Included(10)), 1);
+        map.assign((Excluded(20), Excluded(25)), 1);
+        assert_eq!(
+            map.iter().collect::<Vec<_>>(),
+            vec![
+                (&(Unbounded, Excluded(5)), &0),
+                (&(Included(5), Excluded(25)), &1),
+                (&(Included(25), Unbounded), &0),
+            ]
+        );
+
+        map.assign((Included(0), Unbounded), 2);
+        assert_eq!(
+            map.iter().collect::<Vec<_>>(),
+            vec![(&(Unbounded, Excluded(0)), &0), (&(Included(0), Unbounded), &2)]
+        );
+
+        map.assign(.., 3);
+        assert_eq!(map.iter().collect::<Vec<_>>(), vec![(&(Unbounded, Unbounded), &3)]);
+
+        assert!(!map.clear_range((Excluded(0), Excluded(0))));
+        assert!(map.clear_range(..));
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn random_operations_match_pointwise_assignment() {
+        let mut map = PiecewiseMap::default();
+        let mut assigned = points(31, 4).map(|p| (p, None)).collect::<Vec<_>>();
+        for (i, range) in intervals(300, 31, 4) {
+            let value = if i % 5 == 4 { None } else { Some(i % 3) };
+            match value {
+                Some(value) => map.assign(range, value),
+                None => {
+                    map.clear_range(range);
+                }
+            }
+            for (p, point) in assigned.iter_mut() {
+                if range.contains(p) {
+                    *point = value;
+                }
+            }
+
+            // Pieces are disjoint, and adjacent pieces have distinct values.
+            let pieces = map.iter().collect::<Vec<_>>();
+            assert_eq!(pieces.len(), map.len());
+            for pair in pieces.windows(2) {
+                let (end, next_start) = (Cut::from_end(pair[0].0 .1.as_ref()), Cut::from_start(pair[1].0 .0.as_ref()));
+                assert!(end <= next_start);
+                assert!(end < next_start || pair[0].1 != pair[1].1);
+            }
+            for (p, point) in &assigned {
+                assert_eq!(map.get(p), point.as_ref());
+            }
+
+            let query = (Excluded(2 * (i % 29)), Included(2 * (i % 29) + 11));
+            let clipped = map.iter_range(&query).collect::<Vec<_>>();
+            for p in points(31, 4) {
+                let values = clipped
+                    .iter()
+                    .filter(|(piece, _)| piece.contains(&&p))
+                    .map(|(_, value)| *value)
+                    .collect::<Vec<_>>();
+                let expected = map.get(&p).filter(|_| query.contains(&p));
+                assert_eq!(values, expected.into_iter().collect::<Vec<_>>());
+            }
+        }
+    }
+}
@@ -0,0 +1,590 @@
+use crate::interval_tree::{Gaps, IntervalError, IntervalTree, OverlapIter};
+use crate::node::{Node, NodeIter, Range};
+use crate::set_ops::Cut;
+
+use std::borrow::Borrow;
+use std::fmt;
+use std::ops::Bound::*;
+use std::ops::RangeBounds;
+#[cfg(any(feature="serde", test))]
+use serde::{Serialize, Deserialize};
+
+/// A set of points stored as intervals, which are kept in a canonical form: overlapping
+/// and adjacent intervals are merged when inserted, and intervals are split when
+/// some of their points are removed.
+///
+/// Intervals are adjacent when no point lies between them, such as `[1, 5)` and `[5, 9]`,
+/// or `[1, 5]` and `(5, 9]`. Hence, the intervals stored are always disjoint and
+/// non-adjacent, so that every interval is a maximal run of points of the set.
+/// It is built on the same data structure as [`IntervalTree`].
+///
+/// ```
+/// use std::ops::Bound::{Included, Excluded};
+/// use unbounded_interval_tree::range_set::RangeSet;
+///
+/// let mut set = RangeSet::default();
+///
+/// set.insert(1..5);
+/// set.insert(5..=9);
+/// set.insert(20..30);
+///
+/// assert_eq!(set.iter().collect::<Vec<_>>(),
+///            vec![&(Included(1), Included(9)), &(Included(20), Excluded(30))]);
+///
+/// set.remove(&(3..25));
+///
+/// assert_eq!(set.iter().collect::<Vec<_>>(),
+///            vec![&(Included(1), Excluded(3)), &(Included(25), Excluded(30))]);
+/// ```
+#[cfg_attr(any(feature="serde", test), derive(Serialize, Deserialize))]
+#[cfg_attr(
+    any(feature="serde", test),
+    serde(
+        from = "IntervalTree<K>",
+        bound(deserialize = "K: Deserialize<'de> + Ord + Clone")
+    )
+)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct RangeSet<K> {
+    root: Option<Box<Node<K>>>,
+    size: usize,
+}
+
+impl<K> fmt::Display for RangeSet<K>
+where
+    K: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.root {
+            Some(ref root) => write!(f, "{}", root),
+            None => write!(f, "Empty set"),
+        }
+    }
+}
+
+impl<K> Default for RangeSet<K> {
+    fn default() -> RangeSet<K> {
+        RangeSet {
+            root: None,
+            size: 0,
+        }
+    }
+}
+
+impl<K> Drop for RangeSet<K> {
+    fn drop(&mut self) {
+        Node::drop_tree(self.root.take());
+    }
+}
+
+/// Creates a [`RangeSet`] from an iterator of elements satisfying the [`RangeBounds`] trait.
+/// Overlapping and adjacent intervals are merged.
+impl<K, R> FromIterator<R> for RangeSet<K>
+where
+    K: Ord + Clone,
+    R: RangeBounds<K>,
+{
+    fn from_iter<T: IntoIterator<Item = R>>(iter: T) -> Self {
+        let mut range_set = Self::default();
+
+        for interval in iter {
+            range_set.insert(interval);
+        }
+
+        range_set
+    }
+}
+
+impl<K, R, const N: usize> From<[R; N]> for RangeSet<K>
+where
+    K: Ord + Clone,
+    R: RangeBounds<K>,
+{
+    fn from(intervals: [R; N]) -> Self {
+        intervals.into_iter().collect()
+    }
+}
+
+/// Creates a [`RangeSet`] with the points covered by the intervals of an [`IntervalTree`].
+///
+/// # Examples
+///
+/// ```
+/// use std::ops::Bound::Included;
+/// use unbounded_interval_tree::interval_tree::IntervalTree;
+/// use unbounded_interval_tree::range_set::RangeSet;
+///
+/// let tree = IntervalTree::from([0..=5, 3..=9]);
+/// let set = RangeSet::from(tree);
+///
+/// assert_eq!(set.iter().collect::<Vec<_>>(), vec![&(Included(0), Included(9))]);
+/// ```
+impl<K> From<IntervalTree<K>> for RangeSet<K>
+where
+    K: Ord + Clone,
+{
+    fn from(tree: IntervalTree<K>) -> Self {
+        tree.iter().cloned().collect()
+    }
+}
+
+impl<K> RangeSet<K> {
+    /// Produces an inorder iterator over the disjoint and non-adjacent intervals of the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::ops::Bound::{Included, Excluded};
+    /// use unbounded_interval_tree::range_set::RangeSet;
+    ///
+    /// let set = RangeSet::from([(Included(5), Excluded(9)), (Excluded(9), Included(12))]);
+    ///
+    /// let mut iter = set.iter();
+    /// assert_eq!(iter.next(), Some(&(Included(5), Excluded(9))));
+    /// assert_eq!(iter.next(), Some(&(Excluded(9), Included(12))));
+    /// assert_eq!(iter.next(), None);
+    /// ```
+    pub fn iter<'a>(&'a self) -> RangeSetIter<'a, K> {
+        RangeSetIter {
+            inner: NodeIter::new(&self.root),
+        }
+    }
+
+    /// Inserts all the points of the interval `range` into the set. The intervals
+    /// of the set overlapping with or adjacent to `range` are merged with it.
+    ///
+    /// Returns whether the set changed, that is whether some points of `range` were not
+    /// already in the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::ops::Bound::{Included, Excluded};
+    /// use unbounded_interval_tree::range_set::RangeSet;
+    ///
+    /// let mut set = RangeSet::default();
+    ///
+    /// assert!(set.insert(1..5));
+    /// assert!(set.insert(8..10));
+    /// assert!(set.insert(5..8));
+    /// assert!(!set.insert(2..=9));
+    ///
+    /// assert_eq!(set.iter().collect::<Vec<_>>(), vec![&(Included(1), Excluded(10))]);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is inverted or empty, like [`IntervalTree::insert`].
+    /// See [`RangeSet::try_insert`] to handle invalid intervals instead.
+    pub fn insert<R>(&mut self, range: R) -> bool
+    where
+        K: Ord + Clone,
+        R: RangeBounds<K>,
+    {
+        match self.try_insert(range) {
+            Ok(inserted) => inserted,
+            Err(err) => panic!("cannot insert an invalid interval: {}", err),
+        }
+    }
+
+    /// Inserts all the points of the interval `range` into the set, like [`RangeSet::insert`],
+    /// unless `range` is invalid. See [`IntervalTree::try_insert`] for more details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unbounded_interval_tree::interval_tree::IntervalError;
+    /// use unbounded_interval_tree::range_set::RangeSet;
+    ///
+    /// let mut set = RangeSet::default();
+    ///
+    /// assert_eq!(set.try_insert(1..5), Ok(true));
+    /// assert_eq!(set.try_insert(5..5), Err(IntervalError::Empty));
+    /// ```
+    pub fn try_insert<R>(&mut self, range: R) -> Result<bool, IntervalError>
+    where
+        K: Ord + Clone,
+        R: RangeBounds<K>,
+    {
+        let range = (range.start_bound().cloned(), range.end_bound().cloned());
+        IntervalTree::validate(&range)?;
+
+        let mut start = Cut::from_start(range.0);
+        let mut end = Cut::from_end(range.1);
+
+        // The intervals touching `range` are merged one at a time into it.
+        while let Some(touching) = IntervalTree::find_disjoint(&self.root, start.as_ref(), end.as_ref(), true) {
+            let touching = &touching.key;
+            let touching_start = Cut::from_start(touching.0.as_ref());
+            let touching_end = Cut::from_end(touching.1.as_ref());
+
+            // Intervals are disjoint, so only the first interval found may contain `range`.
+            if touching_start <= start.as_ref() && end.as_ref() <= touching_end {
+                return Ok(false);
+            }
+            if touching_start < start.as_ref() {
+                start = touching_start.cloned();
+            }
+            if touching_end > end.as_ref() {
+                end = touching_end.cloned();
+            }
+
+            let touching = touching.clone();
+            IntervalTree::remove_rec(&mut self.root, &touching);
+            self.size -= 1;
+        }
+
+        IntervalTree::insert_rec(&mut self.root, (start.into_start(), end.into_end()), ());
+        self.size += 1;
+
+        Ok(true)
+    }
+
+    /// Removes all the points of the interval `range` from the set. The intervals
+    /// of the set partially overlapping with `range` are split accordingly.
+    ///
+    /// Returns whether the set changed, that is whether some points of `range` were in the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::ops::Bound::{Included, Excluded};
+    /// use unbounded_interval_tree::range_set::RangeSet;
+    ///
+    /// let mut set = RangeSet::from([0..=10]);
+    ///
+    /// assert!(set.remove(&(3..5)));
+    /// assert!(!set.remove(&(3..5)));
+    ///
+    /// assert_eq!(set.iter().collect::<Vec<_>>(),
+    ///            vec![&(Included(0), Excluded(3)), &(Included(5), Included(10))]);
+    /// ```
+    pub fn remove<R>(&mut self, range: &R) -> bool
+    where
+        K: Ord + Clone,
+        R: RangeBounds<K>,
+    {
+        let start = Cut::from_start(range.start_bound());
+        let end = Cut::from_end(range.end_bound());
+
+        // An inverted or empty range contains no point to remove.
+        if start >= end {
+            return false;
+        }
+
+        let mut removed = false;
+        while let Some(overlap) = IntervalTree::find_disjoint(&self.root, start, end, false) {
+            let overlap = overlap.key.clone();
+            IntervalTree::remove_rec(&mut self.root, &overlap);
+            self.size -= 1;
+            removed = true;
+
+            // Keep the parts of the removed interval lying outside of `range`.
+            let overlap_start = Cut::from_start(overlap.0);
+            let overlap_end = Cut::from_end(overlap.1);
+            if overlap_start.as_ref() < start {
+                IntervalTree::insert_rec(
+                    &mut self.root,
+                    (overlap_start.into_start(), start.cloned().into_end()),
+                    (),
+                );
+                self.size += 1;
+            }
+            if end < overlap_end.as_ref() {
+                IntervalTree::insert_rec(
+                    &mut self.root,
+                    (end.cloned().into_start(), overlap_end.into_end()),
+                    (),
+                );
+                self.size += 1;
+            }
+        }
+
+        removed
+    }
+
+    /// Returns whether or not the point `p` is in the set.
+    ///
+    /// The given point may be of a borrowed form of the stored type `K`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unbounded_interval_tree::range_set::RangeSet;
+    ///
+    /// let set = RangeSet::from([0..5, 5..10]);
+    ///
+    /// assert!(set.contains_point(&5));
+    /// assert!(!set.contains_point(&10));
+    /// ```
+    pub fn contains_point<Q>(&self, p: &Q) -> bool
+    where
+        K: Ord + Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.contains_interval(&(Included(p), Included(p)))
+    }
+
+    /// Returns whether or not all the points of the interval `range` are in the set.
+    ///
+    /// The given `range` may have bounds that are of a borrowed form of the stored type `K`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unbounded_interval_tree::range_set::RangeSet;
+    ///
+    /// let set = RangeSet::from([0..5, 5..10]);
+    ///
+    /// assert!(set.contains_interval(&(2..8)));
+    /// assert!(!set.contains_interval(&(2..=10)));
+    /// ```
+    pub fn contains_interval<Q, R>(&self, range: &R) -> bool
+    where
+        K: Ord + Borrow<Q>,
+        R: RangeBounds<Q>,
+        Q: Ord + ?Sized,
+    {
+        IntervalTree::covers(&self.root, range)
+    }
+
+    /// Returns an inorder iterator over the intervals of the set that overlap
+    /// with the given `range` (partially or completely).
+    /// See [`IntervalTree::overlaps`] for more details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::ops::Bound::{Included, Excluded};
+    /// use unbounded_interval_tree::range_set::RangeSet;
+    ///
+    /// let set = RangeSet::from([0..5, 5..10, 20..30]);
+    ///
+    /// assert_eq!(set.overlaps(&(8..25)).collect::<Vec<_>>(),
+    ///            vec![&(Included(0), Excluded(10)), &(Included(20), Excluded(30))]);
+    /// ```
+    pub fn overlaps<'a, Q, R>(&'a self, range: &'a R) -> impl Iterator<Item = &'a Range<K>>
+    where
+        K: Ord + Borrow<Q>,
+        R: RangeBounds<Q>,
+        Q: Ord + ?Sized + 'a,
+    {
+        OverlapIter::new(&self.root, range.start_bound(), range.end_bound()).map(|node| &node.key)
+    }
+
+    /// Returns an iterator over the ordered subintervals in `range` that are not in the set.
+    /// See [`IntervalTree::gaps`] for more details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::ops::Bound::{Included, Excluded};
+    /// use unbounded_interval_tree::range_set::RangeSet;
+    ///
+    /// let set = RangeSet::from([0..5, 5..10, 20..30]);
+    ///
+    /// assert_eq!(set.gaps(&(0..25)).collect::<Vec<_>>(),
+    ///            vec![(Included(&10), Excluded(&20))]);
+    /// ```
+    pub fn gaps<'a, Q, R>(&'a self, range: &'a R) -> impl Iterator<Item = Range<&'a Q>>
+    where
+        K: Ord + Borrow<Q>,
+        R: RangeBounds<Q>,
+        Q: Ord + ?Sized + 'a,
+    {
+        let (start, end) = (range.start_bound(), range.end_bound());
+        let overlaps = OverlapIter::new(&self.root, start, end).map(|node| &node.key);
+
+        Gaps::new(overlaps, start, end)
+    }
+
+    /// Returns the number of disjoint and non-adjacent intervals in the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unbounded_interval_tree::range_set::RangeSet;
+    ///
+    /// let set = RangeSet::from([0..5, 5..10, 20..30]);
+    ///
+    /// assert_eq!(set.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.size
+    }
+
+    /// Returns `true` if the set contains no point.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unbounded_interval_tree::range_set::RangeSet;
+    ///
+    /// let mut set = RangeSet::default();
+    ///
+    /// assert!(set.is_empty());
+    ///
+    /// set.insert(0..5);
+    ///
+    /// assert!(!set.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Clear the set, removing all points.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unbounded_interval_tree::range_set::RangeSet;
+    ///
+    /// let mut set = RangeSet::from([0..5, 20..30]);
+    ///
+    /// set.clear();
+    ///
+    /// assert!(set.is_empty());
+    /// ```
+    pub fn clear(&mut self) {
+        Node::drop_tree(self.root.take());
+        self.size = 0;
+    }
+}
+
+/// An inorder interator through the intervals of a range set.
+pub struct RangeSetIter<'a, K> {
+    inner: NodeIter<'a, K, ()>,
+}
+
+impl<'a, K> Iterator for RangeSetIter<'a, K> {
+    type Item = &'a Range<K>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|node| &node.key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{intervals, points};
+    use serde_json::{from_str, to_string};
+
+    #[test]
+    fn serialize_deserialize_identity() {
+        let mut set = RangeSet::default();
+        let serialized_empty_set = to_string(&set).unwrap();
+        let deserialized_empty_set = from_str(&serialized_empty_set).unwrap();
+        assert_eq!(set, deserialized_empty_set);
+
+        set.insert((Included(1), Excluded(3)));
+        set.insert((Excluded(5), Included(8)));
+        let serialized_set = to_string(&set).unwrap();
+        let deserialized_set = from_str(&serialized_set).unwrap();
+        assert_eq!(set, deserialized_set);
+
+        // A serialized interval tree is deserialized into its canonical form.
+        let tree = IntervalTree::from([0..5, 5..10]);
+        let deserialized_set: RangeSet<_> = from_str(&to_string(&tree).unwrap()).unwrap();
+        assert_eq!(deserialized_set, RangeSet::from([(Included(0), Excluded(10))]));
+
+        // The size is recomputed, and unsorted intervals are rejected.
+        let serialized_set = serialized_set.replace(r#""size":2"#, r#""size":42"#);
+        let deserialized_set: RangeSet<i32> = from_str(&serialized_set).unwrap();
+        assert_eq!(deserialized_set.len(), 2);
+        let unsorted_set = serialized_set.replace(r#"{"Excluded":5}"#, r#"{"Excluded":0}"#);
+        let err = from_str::<RangeSet<i32>>(&unsorted_set).unwrap_err();
+        assert!(err.to_string().contains("the intervals are not sorted"));
+    }
+
+    #[test]
+    fn insert_merges_contiguous_intervals() {
+        let cases = [
+            // Included+Excluded at the same point is contiguous.
+            ((Included(1), Excluded(5)), (Included(5), Included(9)), vec![(Included(1), Included(9))]),
+            ((Included(1), Included(5)), (Excluded(5), Included(9)), vec![(Included(1), Included(9))]),
+            // Excluded+Excluded at the same point is not.
+            (
+                (Included(1), Excluded(5)),
+                (Excluded(5), Included(9)),
+                vec![(Included(1), Excluded(5)), (Excluded(5), Included(9))],
+            ),
+            ((Included(1), Included(5)), (Included(5), Included(9)), vec![(Included(1), Included(9))]),
+            ((Included(1), Included(9)), (Included(3), Included(5)), vec![(Included(1), Included(9))]),
+            ((Included(3), Included(5)), (Unbounded, Excluded(3)), vec![(Unbounded, Included(5))]),
+            ((Included(3), Included(5)), (Excluded(5), Unbounded), vec![(Included(3), Unbounded)]),
+        ];
+
+        for (first, second, expected) in cases {
+            for (a, b) in [(first, second), (second, first)] {
+                let set = RangeSet::from([a, b]);
+                assert_eq!(set.iter().cloned().collect::<Vec<_>>(), expected);
+                assert_eq!(set.len(), expected.len());
+            }
+        }
+
+        let mut set = RangeSet::from([0..2, 4..6, 8..10, 12..14]);
+        assert!(set.insert(1..=12));
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![&(Included(0), Excluded(14))]);
+        assert_eq!(set.len(), 1);
+    }
+
+    #[test]
+    fn remove_splits_intervals() {
+        let mut set = RangeSet::from([(Unbounded::<i32>, Unbounded)]);
+
+        assert!(set.remove(&(Included(0), Excluded(10))));
+        assert!(set.remove(&(Excluded(20), Included(30))));
+        assert!(!set.remove(&(Included(5), Excluded(10))));
+        assert!(!set.remove(&(Excluded(5), Excluded(5))));
+        assert_eq!(
+            set.iter().collect::<Vec<_>>(),
+            vec![
+                &(Unbounded, Excluded(0)),
+                &(Included(10), Included(20)),
+                &(Excluded(30), Unbounded)
+            ]
+        );
+
+        assert!(set.remove(&(Included(-5), Excluded(40))));
+        assert_eq!(
+            set.iter().collect::<Vec<_>>(),
+            vec![&(Unbounded, Excluded(-5)), &(Included(40), Unbounded)]
+        );
+
+        assert!(set.remove(&(..)));
+        assert!(set.is_empty());
+    }
+
+    #[test]
+    fn random_operations_keep_canonical_form() {
+        let mut set = RangeSet::default();
+        let mut points = points(31, 4).map(|p| (p, false)).collect::<Vec<_>>();
+        for (i, range) in intervals(300, 31, 4) {
+            let changed = if i % 4 == 3 {
+                set.remove(&range)
+            } else {
+                set.insert(range)
+            };
+
+            let mut expected_changed = false;
+            for (p, in_set) in points.iter_mut() {
+                if range.contains(p) && *in_set != (i % 4 != 3) {
+                    *in_set = !*in_set;
+                    expected_changed = true;
+                }
+            }
+            assert_eq!(changed, expected_changed);
+
+            // Intervals are disjoint and non-adjacent.
+            let ranges = set.iter().collect::<Vec<_>>();
+            assert_eq!(ranges.len(), set.len());
+            for pair in ranges.windows(2) {
+                assert!(Cut::from_end(pair[0].1.as_ref()) < Cut::from_start(pair[1].0.as_ref()));
+            }
+            for (p, in_set) in &points {
+                assert_eq!(set.contains_point(p), *in_set);
+            }
+        }
+    }
+}
@@ -0,0 +1,241 @@
+use crate::node::Range;
+
+use std::cmp::Ordering;
+use std::iter::Peekable;
+use std::ops::Bound;
+use std::ops::Bound::*;
+
+/// A cut of the line of keys, lying between two points. Any bound of an interval
+/// can be seen as a cut: the interval is the set of points between its two cuts.
+/// For instance, both `Included(x)` as a start bound and `Excluded(x)` as an end bound
+/// are the cut just below `x`.
+///
+/// Working with cuts rather than bounds makes contiguity explicit: two intervals are
+/// contiguous if and only if the start cut of one is smaller or equal to the end cut of the other.
+/// This is how an Included+Excluded at the same point is contiguous.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Cut<T> {
+    BelowAll,
+    Below(T),
+    Above(T),
+    AboveAll,
+}
+
+impl<T> Cut<T> {
+    pub fn from_start(bound: Bound<T>) -> Cut<T> {
+        match bound {
+            Included(x) => Cut::Below(x),
+            Excluded(x) => Cut::Above(x),
+            Unbounded => Cut::BelowAll,
+        }
+    }
+
+    pub fn from_end(bound: Bound<T>) -> Cut<T> {
+        match bound {
+            Included(x) => Cut::Above(x),
+            Excluded(x) => Cut::Below(x),
+            Unbounded => Cut::AboveAll,
+        }
+    }
+
+    pub fn as_ref(&self) -> Cut<&T> {
+        match self {
+            Cut::BelowAll => Cut::BelowAll,
+            Cut::Below(x) => Cut::Below(x),
+            Cut::Above(x) => Cut::Above(x),
+            Cut::AboveAll => Cut::AboveAll,
+        }
+    }
+
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Cut<U> {
+        match self {
+            Cut::BelowAll => Cut::BelowAll,
+            Cut::Below(x) => Cut::Below(f(x)),
+            Cut::Above(x) => Cut::Above(f(x)),
+            Cut::AboveAll => Cut::AboveAll,
+        }
+    }
+
+    pub fn into_start(self) -> Bound<T> {
+        match self {
+            Cut::Below(x) => Included(x),
+            Cut::Above(x) => Excluded(x),
+            Cut::BelowAll | Cut::AboveAll => Unbounded,
+        }
+    }
+
+    pub fn into_end(self) -> Bound<T> {
+        match self {
+            Cut::Above(x) => Included(x),
+            Cut::Below(x) => Excluded(x),
+            Cut::BelowAll | Cut::AboveAll => Unbounded,
+        }
+    }
+}
+
+impl<T: Clone> Cut<&T> {
+    pub fn cloned(self) -> Cut<T> {
+        match self {
+            Cut::BelowAll => Cut::BelowAll,
+            Cut::Below(x) => Cut::Below(x.clone()),
+            Cut::Above(x) => Cut::Above(x.clone()),
+            Cut::AboveAll => Cut::AboveAll,
+        }
+    }
+}
+
+impl<T: Ord> PartialOrd for Cut<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: Ord> Ord for Cut<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.encode().cmp(&other.encode())
+    }
+}
+
+impl<T> Cut<T> {
+    // Similar to the encoding in `IntervalTree::cmp`, where the cut below x
+    // comes before the cut above x.
+    fn encode(&self) -> (u8, Option<(&T, u8)>) {
+        match self {
+            Cut::BelowAll => (0, None),
+            Cut::Below(x) => (1, Some((x, 1))),
+            Cut::Above(x) => (1, Some((x, 2))),
+            Cut::AboveAll => (2, None),
+        }
+    }
+}
+
+/// An iterator merging the contiguous intervals of an iterator of intervals sorted by
+/// their start bound, yielding the start and end cuts of disjoint and non-adjacent intervals.
+pub(crate) struct Runs<'a, K: 'a, I: Iterator<Item = &'a Range<K>>> {
+    inner: Peekable<I>,
+}
+
+impl<'a, K, I> Runs<'a, K, I>
+where
+    K: 'a,
+    I: Iterator<Item = &'a Range<K>>,
+{
+    pub fn new(inner: I) -> Self {
+        Runs {
+            inner: inner.peekable(),
+        }
+    }
+}
+
+impl<'a, K, I> Iterator for Runs<'a, K, I>
+where
+    K: Ord + 'a,
+    I: Iterator<Item = &'a Range<K>>,
+{
+    type Item = (Cut<&'a K>, Cut<&'a K>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (start, end) = self.inner.next()?;
+        let start = Cut::from_start(start.as_ref());
+        let mut end = Cut::from_end(end.as_ref());
+
+        while let Some((next_start, next_end)) = self.inner.peek() {
+            if Cut::from_start(next_start.as_ref()) > end {
+                break;
+            }
+
+            end = end.max(Cut::from_end(next_end.as_ref()));
+            self.inner.next();
+        }
+
+        Some((start, end))
+    }
+}
+
+/// An iterator computing a set operation between two iterators of disjoint and
+/// non-adjacent intervals, given as cuts. It sweeps the cuts of both operands in order,
+/// and a point is kept if `op` holds for its membership in each operand.
+/// The intervals yielded are themselves disjoint and non-adjacent.
+pub(crate) struct SetOp<A: Iterator, B: Iterator> {
+    a: Peekable<A>,
+    b: Peekable<B>,
+    // Whether the sweep is currently within an interval of `a` (resp. `b`),
+    // in which case the next cut of `a` (resp. `b`) is an end cut.
+    in_a: bool,
+    in_b: bool,
+    op: fn(bool, bool) -> bool,
+}
+
+impl<T, A, B> SetOp<A, B>
+where
+    T: Ord + Copy,
+    A: Iterator<Item = (Cut<T>, Cut<T>)>,
+    B: Iterator<Item = (Cut<T>, Cut<T>)>,
+{
+    pub fn new(a: A, b: B, op: fn(bool, bool) -> bool) -> Self {
+        SetOp {
+            a: a.peekable(),
+            b: b.peekable(),
+            in_a: false,
+            in_b: false,
+            op,
+        }
+    }
+
+    // Returns the next cut of the sweep along with the membership of the points following it.
+    fn next_cut(&mut self) -> Option<(Cut<T>, bool)> {
+        let next_a = self.a.peek().map(|&(start, end)| if self.in_a { end } else { start });
+        let next_b = self.b.peek().map(|&(start, end)| if self.in_b { end } else { start });
+
+        let cut = match (next_a, next_b) {
+            (None, None) => return None,
+            (Some(cut), None) | (None, Some(cut)) => cut,
+            (Some(cut_a), Some(cut_b)) => cut_a.min(cut_b),
+        };
+
+        // Cuts are strictly increasing in each operand, so at most one cut
+        // of each operand can be crossed at once.
+        if next_a == Some(cut) {
+            if self.in_a {
+                self.a.next();
+            }
+            self.in_a = !self.in_a;
+        }
+        if next_b == Some(cut) {
+            if self.in_b {
+                self.b.next();
+            }
+            self.in_b = !self.in_b;
+        }
+
+        Some((cut, (self.op)(self.in_a, self.in_b)))
+    }
+}
+
+impl<T, A, B> Iterator for SetOp<A, B>
+where
+    T: Ord + Copy,
+    A: Iterator<Item = (Cut<T>, Cut<T>)>,
+    B: Iterator<Item = (Cut<T>, Cut<T>)>,
+{
+    type Item = Range<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // Points before the first cut belong to neither operand, and `op` never keeps them.
+        let start = loop {
+            match self.next_cut()? {
+                (cut, true) => break cut,
+                (_, false) => continue,
+            }
+        };
+
+        loop {
+            match self.next_cut() {
+                Some((_, true)) => continue,
+                Some((end, false)) => return Some((start.into_start(), end.into_end())),
+                // Both operands are exhausted, so the sweep is outside of both of them.
+                None => unreachable!("a set operation never keeps points outside of both operands"),
+            }
+        }
+    }
+}
@@ -0,0 +1,32 @@
+use crate::interval_tree::IntervalTree;
+use crate::node::Range;
+
+use std::ops::Bound;
+use std::ops::Bound::*;
+
+/// Returns the bound at key `2 * i` whose kind is picked by `kind`.
+/// Keys are even, so that odd points lie strictly between keys.
+pub(crate) fn bound(i: i32, kind: i32) -> Bound<i32> {
+    match kind % 3 {
+        0 => Included(2 * i),
+        1 => Excluded(2 * i),
+        _ => Unbounded,
+    }
+}
+
+/// Generates up to `n` valid intervals, along with the index they were generated at.
+/// The interval at index `i` starts at `(i * 7) % width` and spans up to `spread - 1` keys,
+/// with every kind of bounds.
+pub(crate) fn intervals(n: i32, width: i32, spread: i32) -> impl Iterator<Item = (i32, Range<i32>)> {
+    (0..n).filter_map(move |i| {
+        let start = (i * 7) % width;
+        let range = (bound(start, i), bound(start + i % spread, i / 3));
+        IntervalTree::validate(&range).ok().map(|()| (i, range))
+    })
+}
+
+/// Returns the points around the keys of the intervals generated by [`intervals`],
+/// from a few points before the first key to a few points after the last one.
+pub(crate) fn points(width: i32, spread: i32) -> std::ops::Range<i32> {
+    -4..2 * (width + spread) + 2
+}